@@ -0,0 +1,379 @@
+use crate::constraint::{
+    ConfigurableConstraint, Constraint, GivenDigitConstraint, KillerCageConstraint,
+    RenbanConstraint, CONFIGURABLES,
+};
+use crate::solver::{
+    base_constraints, count_assertions, NamedConstraint, SolveStatistics, StatisticsRecorder,
+};
+use crate::sudoku::{Cell, Encoding, SudokuContext, SUDOKU_SIZE};
+use crate::z3_helper::Assertions;
+use std::time::Duration;
+
+// A puzzle from a corpus, with the variant constraints listed after it.
+struct Puzzle {
+    line_number: usize,
+    givens: Vec<GivenDigitConstraint>,
+    constraints: Vec<Box<dyn ConfigurableConstraint + Send>>,
+}
+
+// Times solving every puzzle in a corpus with each encoding. The corpus has one puzzle per line,
+// with the cells read left to right and top to bottom and '.' or '0' for empty cells. A puzzle can
+// be followed by variant constraints, one per line, starting with '+' (see `parse_constraint`).
+// Lines starting with '#' are ignored. With `json`, the full statistics of each run are printed
+// as one JSON object per line instead of a table.
+pub fn run(corpus_path: &str, json: bool) {
+    let corpus = match std::fs::read_to_string(corpus_path) {
+        Ok(corpus) => corpus,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", corpus_path, err);
+            std::process::exit(1);
+        }
+    };
+
+    let puzzles = parse_corpus(&corpus);
+
+    if !json {
+        print!("{:>12}", "puzzle");
+        for encoding in Encoding::ALL {
+            print!(" {:>12}", encoding.name());
+        }
+        println!();
+    }
+
+    let mut totals = [Duration::ZERO; Encoding::ALL.len()];
+    for puzzle in puzzles.iter().flatten() {
+        let case = puzzle.line_number.to_string();
+        if !json {
+            print!("{:>12}", case);
+        }
+        for (encoding, total) in Encoding::ALL.into_iter().zip(&mut totals) {
+            let constraints = puzzle
+                .constraints
+                .iter()
+                .map(|constraint| (constraint.name(), constraint.dyn_clone()))
+                .collect();
+            let (result, statistics) = time_solve(&puzzle.givens, constraints, encoding);
+            *total += statistics.wall_time;
+            print_run(json, &case, encoding.name(), result, &statistics);
+        }
+        if !json {
+            println!();
+        }
+    }
+
+    if !json {
+        print!("{:>12}", "total");
+        for total in totals {
+            print!(" {:>10.1}ms", total.as_secs_f64() * 1000.0);
+        }
+        println!();
+    }
+}
+
+struct QuantifiedRenban(RenbanConstraint);
+
+impl Constraint for QuantifiedRenban {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        self.0.apply_quantified(solver, context);
+    }
+}
+
+// Times filling an empty grid with several renbans, comparing the quantified renban encoding
+// against the quantifier-free ones.
+pub fn run_renban(json: bool) {
+    let layouts: [(&str, Vec<Vec<Cell>>); 3] = [
+        (
+            "rows",
+            (0..SUDOKU_SIZE)
+                .map(|row| {
+                    let first_col = row % 3 * 3;
+                    (first_col..first_col + 3)
+                        .map(|col| Cell::new(row, col))
+                        .collect()
+                })
+                .collect(),
+        ),
+        (
+            "diagonals",
+            vec![
+                (0..5).map(|i| Cell::new(i, i)).collect(),
+                (0..4).map(|i| Cell::new(i, 8 - i)).collect(),
+                (0..4).map(|i| Cell::new(8 - i, i)).collect(),
+                (0..4).map(|i| Cell::new(8 - i, 8 - i)).collect(),
+                (5..8).map(|row| Cell::new(row, 4)).collect(),
+            ],
+        ),
+        (
+            "boxes",
+            (0..SUDOKU_SIZE)
+                .map(|i| {
+                    let (row, col) = (i / 3 * 3, i % 3 * 3);
+                    vec![
+                        Cell::new(row, col),
+                        Cell::new(row + 1, col),
+                        Cell::new(row + 2, col),
+                        Cell::new(row + 2, col + 1),
+                    ]
+                })
+                .collect(),
+        ),
+    ];
+
+    let variants = [
+        ("Quantified", true, Encoding::Integer),
+        ("QF integer", false, Encoding::Integer),
+        ("QF one-hot", false, Encoding::OneHot),
+    ];
+    if !json {
+        print!("{:>12}", "layout");
+        for (variant, _, _) in variants {
+            print!(" {:>12}", variant);
+        }
+        println!();
+    }
+    for (name, renbans) in layouts {
+        if !json {
+            print!("{:>12}", name);
+        }
+        for (variant, quantified, encoding) in variants {
+            let constraints = renbans
+                .iter()
+                .map(|cells| {
+                    let renban = RenbanConstraint::new(cells.clone());
+                    let constraint: Box<dyn Constraint + Send> = if quantified {
+                        Box::new(QuantifiedRenban(renban))
+                    } else {
+                        Box::new(renban)
+                    };
+                    ("Renban", constraint)
+                })
+                .collect();
+            let (result, statistics) = time_solve(&[], constraints, encoding);
+            print_run(json, name, variant, result, &statistics);
+        }
+        if !json {
+            println!();
+        }
+    }
+}
+
+fn print_run(
+    json: bool,
+    case: &str,
+    variant: &str,
+    result: z3::SatResult,
+    statistics: &SolveStatistics,
+) {
+    if json {
+        println!(
+            "{{\"case\":\"{}\",\"variant\":\"{}\",\"result\":\"{:?}\",\"statistics\":{}}}",
+            case,
+            variant,
+            result,
+            statistics.to_json()
+        );
+    } else {
+        print!(" {:>10.1}ms", statistics.wall_time.as_secs_f64() * 1000.0);
+        if result != z3::SatResult::Sat {
+            print!(" ({:?})", result);
+        }
+    }
+}
+
+// Parses a corpus in the format described at `run`. Invalid puzzles are kept as `None`, so that
+// their constraints aren't added to the puzzle before them.
+fn parse_corpus(corpus: &str) -> Vec<Option<Puzzle>> {
+    let mut puzzles: Vec<Option<Puzzle>> = Vec::new();
+    for (line_number, line) in corpus.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('+') {
+            match (puzzles.last_mut(), parse_constraint(line)) {
+                (Some(Some(puzzle)), Some(constraint)) => puzzle.constraints.push(constraint),
+                // The puzzle has already been reported.
+                (Some(None), _) => {}
+                _ => eprintln!("Skipping invalid constraint on line {}", line_number + 1),
+            }
+            continue;
+        }
+        let givens = parse_puzzle(line);
+        if givens.is_none() {
+            eprintln!("Skipping invalid puzzle on line {}", line_number + 1);
+        }
+        puzzles.push(givens.map(|givens| Puzzle {
+            line_number: line_number + 1,
+            givens,
+            constraints: Vec::new(),
+        }));
+    }
+    puzzles
+}
+
+fn parse_puzzle(line: &str) -> Option<Vec<GivenDigitConstraint>> {
+    if line.chars().count() != SUDOKU_SIZE * SUDOKU_SIZE {
+        return None;
+    }
+    let mut givens = Vec::new();
+    for (i, c) in line.chars().enumerate() {
+        match c {
+            '.' | '0' => {}
+            '1'..='9' => givens.push(GivenDigitConstraint {
+                row: i / SUDOKU_SIZE,
+                col: i % SUDOKU_SIZE,
+                value: c.to_digit(10).unwrap() as i32,
+            }),
+            _ => return None,
+        }
+    }
+    Some(givens)
+}
+
+// Parses a constraint line such as "+ Thermo: r1c1 r1c2 r1c3". The constraint is created as if it
+// was added in the UI with the listed cells and default settings, except that killer cages take
+// their total after the name, as in "+ Killer Cage 15: r1c1 r1c2".
+fn parse_constraint(line: &str) -> Option<Box<dyn ConfigurableConstraint + Send>> {
+    let (name, cells) = line.strip_prefix('+')?.split_once(':')?;
+    let cells = cells
+        .split_whitespace()
+        .map(|cell| cell.parse().ok())
+        .collect::<Option<Vec<Cell>>>()?;
+    let name = name.trim();
+    let constraint: Box<dyn ConfigurableConstraint + Send> = match name.strip_prefix("Killer Cage ")
+    {
+        Some(total) => Box::new(KillerCageConstraint::new(cells, total.trim().parse().ok()?)),
+        None => {
+            let mut constraint = CONFIGURABLES.get(name)?();
+            *constraint.get_highlighted_cells()? = cells;
+            constraint
+        }
+    };
+    constraint.is_valid().then_some(constraint)
+}
+
+// Solves from scratch on a fresh context, so that runs don't benefit from each other's learned
+// clauses. The time includes asserting the constraints.
+fn time_solve(
+    givens: &[GivenDigitConstraint],
+    extra_constraints: Vec<NamedConstraint>,
+    encoding: Encoding,
+) -> (z3::SatResult, SolveStatistics) {
+    let ctx = z3::Context::new(&z3::Config::new());
+    let solver = z3::Solver::new(&ctx);
+    let recorder = StatisticsRecorder::start(&solver);
+
+    let (names, constraints): (Vec<_>, Vec<_>) = base_constraints()
+        .into_iter()
+        .chain(extra_constraints)
+        .unzip();
+    let sudoku = SudokuContext::create(&ctx, &constraints, encoding);
+    let mut assertions = names
+        .into_iter()
+        .zip(&constraints)
+        .map(|(name, constraint)| {
+            let count =
+                count_assertions(&solver, |solver| constraint.apply_encoded(solver, &sudoku));
+            (name, count)
+        })
+        .collect::<Vec<_>>();
+    let given_assertions = count_assertions(&solver, |solver| {
+        for given in givens {
+            given.apply_encoded(solver, &sudoku);
+        }
+    });
+    assertions.push(("Given digits", given_assertions));
+
+    let result = solver.check();
+    (result, recorder.finish(&solver, 0, assertions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{exclude_solution, get_solution};
+
+    // Returns a solution of the puzzle with the extra givens, if any, and whether it's the only one.
+    fn solve(
+        puzzle: &Puzzle,
+        extra_givens: &[GivenDigitConstraint],
+        encoding: Encoding,
+    ) -> Option<(Vec<i32>, bool)> {
+        let ctx = z3::Context::new(&z3::Config::new());
+        let solver = z3::Solver::new(&ctx);
+        let constraints = base_constraints()
+            .into_iter()
+            .map(|(_, constraint)| constraint)
+            .chain(
+                puzzle
+                    .constraints
+                    .iter()
+                    .map(|constraint| constraint.dyn_clone()),
+            )
+            .collect::<Vec<_>>();
+        let sudoku = SudokuContext::create(&ctx, &constraints, encoding);
+        for constraint in &constraints {
+            count_assertions(&solver, |solver| constraint.apply_encoded(solver, &sudoku));
+        }
+        count_assertions(&solver, |solver| {
+            for given in puzzle.givens.iter().chain(extra_givens) {
+                given.apply_encoded(solver, &sudoku);
+            }
+        });
+
+        if solver.check() != z3::SatResult::Sat {
+            return None;
+        }
+        let solution = get_solution(&solver, &sudoku);
+        exclude_solution(&solver, &sudoku, &solution);
+        let unique = solver.check() == z3::SatResult::Unsat;
+        Some((solution, unique))
+    }
+
+    fn to_givens(solution: &[i32]) -> Vec<GivenDigitConstraint> {
+        solution
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| GivenDigitConstraint {
+                row: i / SUDOKU_SIZE,
+                col: i % SUDOKU_SIZE,
+                value,
+            })
+            .collect()
+    }
+
+    // Checks that both encodings agree on whether each puzzle has a unique solution, and on the
+    // solution if so. Otherwise, each encoding has to accept the solution found by the other.
+    fn check_encodings_agree(corpus: &str, puzzle_count: usize) {
+        let puzzles = parse_corpus(corpus);
+        assert!(puzzles.iter().all(Option::is_some));
+        for puzzle in puzzles.iter().flatten().take(puzzle_count) {
+            let line = puzzle.line_number;
+            let (integer, integer_unique) =
+                solve(puzzle, &[], Encoding::Integer).expect("Puzzle has no solution");
+            let (one_hot, one_hot_unique) =
+                solve(puzzle, &[], Encoding::OneHot).expect("Puzzle has no solution");
+            assert_eq!(
+                integer_unique, one_hot_unique,
+                "Uniqueness differs on line {}",
+                line
+            );
+            if integer_unique {
+                assert_eq!(integer, one_hot, "Solutions differ on line {}", line);
+            } else {
+                assert!(solve(puzzle, &to_givens(&one_hot), Encoding::Integer).is_some());
+                assert!(solve(puzzle, &to_givens(&integer), Encoding::OneHot).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_classic_encodings_agree() {
+        check_encodings_agree(include_str!("../bench/classic.txt"), 3);
+    }
+
+    #[test]
+    fn test_variant_encodings_agree() {
+        check_encodings_agree(include_str!("../bench/variants.txt"), 4);
+    }
+}
@@ -0,0 +1,53 @@
+use eframe::egui::{Context, Ui};
+use crate::constraint::{offsets, ConfigurableConstraint, Constraint};
+use crate::sudoku::{Cell, SudokuContext};
+use macros::DynClone;
+use crate::z3_helper::Assertions;
+
+// Orthogonal neighbours already see each other in their row or column.
+const KING_DELTAS: [(isize, isize); 2] = [(1, 1), (1, -1)];
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct AntiKingConstraint;
+
+impl Constraint for AntiKingConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        offsets::distinct_at_offsets(solver, context, &KING_DELTAS, None);
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        offsets::distinct_at_offsets_one_hot(solver, context, &KING_DELTAS, None);
+    }
+}
+
+impl ConfigurableConstraint for AntiKingConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
+        None
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Anti-King"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, GRID, MIRACLE_GRID};
+
+    #[test]
+    fn test_anti_king() {
+        assert!(satisfies(&[&AntiKingConstraint], MIRACLE_GRID));
+        // R1C3 and R2C4 are both 3s.
+        assert!(!satisfies(&[&AntiKingConstraint], GRID));
+    }
+}
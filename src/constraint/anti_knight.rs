@@ -1,9 +1,8 @@
 use eframe::egui::{Context, Ui};
-use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::constraint::{offsets, ConfigurableConstraint, Constraint};
 use crate::sudoku::{Cell, SudokuContext};
 use macros::DynClone;
-use z3::ast::Ast;
-use z3::Solver;
+use crate::z3_helper::Assertions;
 
 const KNIGHT_DELTAS: [(isize, isize); 4] = [(1, 2), (2, 1), (-1, 2), (-2, 1)];
 
@@ -12,36 +11,18 @@ const KNIGHT_DELTAS: [(isize, isize); 4] = [(1, 2), (2, 1), (-1, 2), (-2, 1)];
 pub struct AntiKnightConstraint;
 
 impl Constraint for AntiKnightConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
-        for row in 0..context.height() {
-            for col in 0..context.width() {
-                for (mut dr, mut dc) in KNIGHT_DELTAS {
-                    dr += row as isize;
-                    dc += col as isize;
-                    if dr < 0
-                        || dc < 0
-                        || dr as usize >= context.height()
-                        || dc as usize >= context.width()
-                    {
-                        continue;
-                    }
-
-                    solver.assert(
-                        context.bools().alloc(
-                            context
-                                .get_cell(row, col)
-                                ._eq(context.get_cell(dr as usize, dc as usize))
-                                .not(),
-                        ),
-                    );
-                }
-            }
-        }
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        offsets::distinct_at_offsets(solver, context, &KNIGHT_DELTAS, None);
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        offsets::distinct_at_offsets_one_hot(solver, context, &KNIGHT_DELTAS, None);
     }
 }
 
 impl ConfigurableConstraint for AntiKnightConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
@@ -0,0 +1,80 @@
+use eframe::egui::{Context, Ui};
+use crate::constraint::{offsets, ConfigurableConstraint, Constraint};
+use crate::sudoku::{Cell, SudokuContext, SUDOKU_SIZE};
+use macros::DynClone;
+use crate::z3_helper::Assertions;
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct AntiQueenConstraint {
+    digit: String,
+}
+
+impl Default for AntiQueenConstraint {
+    fn default() -> Self {
+        Self {
+            digit: "9".to_owned(),
+        }
+    }
+}
+
+impl AntiQueenConstraint {
+    // Orthogonal lines are already covered by rows and columns, so only the diagonals remain.
+    fn deltas(context: &SudokuContext) -> Vec<(isize, isize)> {
+        let size = context.width().max(context.height()) as isize;
+        (1..size).flat_map(|i| [(i, i), (i, -i)]).collect()
+    }
+}
+
+impl Constraint for AntiQueenConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let digit = self.digit.parse().unwrap();
+        offsets::distinct_at_offsets(solver, context, &Self::deltas(context), Some(digit));
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let digit = self.digit.parse().unwrap();
+        offsets::distinct_at_offsets_one_hot(solver, context, &Self::deltas(context), Some(digit));
+    }
+}
+
+impl ConfigurableConstraint for AntiQueenConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Digit");
+            changed |= ui.text_edit_singleline(&mut self.digit).changed();
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
+        None
+    }
+
+    fn is_valid(&self) -> bool {
+        matches!(self.digit.parse::<usize>(), Ok(digit) if (1..=SUDOKU_SIZE).contains(&digit))
+    }
+
+    fn name(&self) -> &'static str {
+        "Anti-Queen"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, GRID};
+
+    // `GRID` with some rows exchanged within their bands and its 3s and 9s swapped, so that no two
+    // 9s share a diagonal.
+    const QUEEN_GRID: &str =
+        "489321657251876439367945821723564198548192376196738245814259763972683514635417982";
+
+    #[test]
+    fn test_anti_queen() {
+        let constraint = AntiQueenConstraint::default();
+        assert!(satisfies(&[&constraint], QUEEN_GRID));
+        assert!(!satisfies(&[&constraint], GRID));
+    }
+}
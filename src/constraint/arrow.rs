@@ -2,11 +2,10 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
-use eframe::egui;
+use crate::z3_helper::Assertions;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
 
 #[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
@@ -15,7 +14,7 @@ pub struct ArrowConstraint {
 }
 
 impl Constraint for ArrowConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         solver.assert(
             context.bools().alloc(
                 z3::ast::Int::add(
@@ -29,10 +28,42 @@ impl Constraint for ArrowConstraint {
             ),
         );
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // The sum is still taken over the integer cells, which are tied to the digit booleans.
+        self.apply(solver, context);
+
+        // Every cell on the arrow holds at least the smallest digit, which bounds the circle from
+        // below and each cell on the arrow from above.
+        let digits = context.digits_range();
+        let arrow_len = self.cells.len() - 1;
+        let circle_min = digits.start() * arrow_len;
+        let arrow_max = digits
+            .end()
+            .saturating_sub(digits.start() * (arrow_len - 1));
+        for (i, cell) in self.cells.iter().enumerate() {
+            for digit in digits.clone() {
+                let impossible = if i == 0 {
+                    digit < circle_min
+                } else {
+                    digit > arrow_max
+                };
+                if impossible {
+                    solver.assert(
+                        context
+                            .bools()
+                            .alloc(context.cell_is(cell.row, cell.col, digit).not()),
+                    );
+                }
+            }
+        }
+    }
 }
 
 impl ConfigurableConstraint for ArrowConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -59,20 +90,18 @@ impl ConfigurableConstraint for ArrowConstraint {
             }
         }
 
-        let stroke = egui::Stroke::new(1f32, context.color);
-
         let circle_radius = 0.4;
         let first_rect = context.cell_rect(self.cells[0].row, self.cells[0].col);
-        context.painter.circle_stroke(
+        context.circle_stroke(
             first_rect.center(),
             first_rect.width() * circle_radius,
-            stroke,
+            1f32,
         );
 
         for (index, [prev, next]) in self.cells.array_windows::<2>().enumerate() {
             let prev_center = context.cell_rect(prev.row, prev.col).center();
             let next_center = context.cell_rect(next.row, next.col).center();
-            context.painter.line_segment(
+            context.line(
                 [
                     if index == 0 {
                         prev_center
@@ -84,22 +113,18 @@ impl ConfigurableConstraint for ArrowConstraint {
                     },
                     next_center,
                 ],
-                stroke,
+                1f32,
             );
 
             if index == self.cells.len() - 2 {
                 // draw the arrow tip
                 let arrow_tip_length = 0.2;
-                let rot = egui::emath::Rot2::from_angle(std::f32::consts::PI * 3.0 / 4.0);
-                let vec = (next_center - prev_center).normalized()
-                    * first_rect.width()
-                    * arrow_tip_length;
-                context
-                    .painter
-                    .line_segment([next_center, next_center + rot * vec], stroke);
-                context
-                    .painter
-                    .line_segment([next_center, next_center + rot.inverse() * vec], stroke);
+                context.arrow_head(
+                    prev_center,
+                    next_center,
+                    1f32,
+                    first_rect.width() * arrow_tip_length,
+                );
             }
         }
     }
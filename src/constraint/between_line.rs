@@ -0,0 +1,95 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct BetweenLineConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for BetweenLineConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let (first, last) = (self.cells[0], self.cells[self.cells.len() - 1]);
+        let first = context.get_cell(first.row, first.col);
+        let last = context.get_cell(last.row, last.col);
+        let ascending = context.bools().alloc(first.lt(last));
+
+        for cell in &self.cells[1..self.cells.len() - 1] {
+            let cell = context.get_cell(cell.row, cell.col);
+            solver.assert(context.bools().alloc(ascending.ite(
+                context.bools().alloc(z3::ast::Bool::and(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(first.lt(cell)),
+                        context.bools().alloc(cell.lt(last)),
+                    ],
+                )),
+                context.bools().alloc(z3::ast::Bool::and(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(last.lt(cell)),
+                        context.bools().alloc(cell.lt(first)),
+                    ],
+                )),
+            )));
+        }
+    }
+}
+
+impl ConfigurableConstraint for BetweenLineConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 3
+    }
+
+    fn name(&self) -> &'static str {
+        "Between Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        if self.cells.len() < 3 {
+            context.default_draw();
+            return;
+        }
+
+        let line_width = 0.1;
+        let circle_radius = 0.4;
+
+        let cell_width = context.cell_rect(0, 0).width();
+        let radius = cell_width * circle_radius;
+        renban::draw_line_with_end_shapes(
+            &self.cells,
+            context,
+            |_| radius,
+            |center| context.circle_stroke(center, radius, cell_width * line_width * 0.5),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_between_line() {
+        // R2C4 to R2C6 read 345.
+        let constraint = BetweenLineConstraint {
+            cells: (3..6).map(|col| sudoku::Cell::new(1, col)).collect(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '4', '5')));
+    }
+}
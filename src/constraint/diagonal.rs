@@ -1,11 +1,11 @@
 use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku::{Cell, SudokuContext};
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 use eframe::egui;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
 
 #[derive(Clone, Eq, PartialEq)]
 enum Direction {
@@ -29,7 +29,7 @@ impl Default for DiagonalConstraint {
 }
 
 impl Constraint for DiagonalConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         if context.width() != context.height() {
             return;
         }
@@ -55,10 +55,32 @@ impl Constraint for DiagonalConstraint {
             );
         }
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        if context.width() != context.height() {
+            return;
+        }
+
+        if self.direction != Direction::Positive {
+            solver.assert(context.one_hot_distinct(
+                &(0..context.width())
+                    .map(|pos| Cell::new(pos, pos))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        if self.direction != Direction::Negative {
+            solver.assert(context.one_hot_distinct(
+                &(0..context.width())
+                    .map(|pos| Cell::new(pos, context.width() - 1 - pos))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+    }
 }
 
 impl ConfigurableConstraint for DiagonalConstraint {
-    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
         ui.horizontal(|ui| {
             ui.label("Direction");
             egui::ComboBox::from_id_source("diagonal_combo")
@@ -68,11 +90,18 @@ impl ConfigurableConstraint for DiagonalConstraint {
                     Direction::Both => "Both",
                 })
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.direction, Direction::Positive, "Positive");
-                    ui.selectable_value(&mut self.direction, Direction::Negative, "Negative");
-                    ui.selectable_value(&mut self.direction, Direction::Both, "Both");
+                    changed |= ui
+                        .selectable_value(&mut self.direction, Direction::Positive, "Positive")
+                        .changed();
+                    changed |= ui
+                        .selectable_value(&mut self.direction, Direction::Negative, "Negative")
+                        .changed();
+                    changed |= ui
+                        .selectable_value(&mut self.direction, Direction::Both, "Both")
+                        .changed();
                 });
         });
+        changed
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
@@ -94,23 +123,23 @@ impl ConfigurableConstraint for DiagonalConstraint {
         }
 
         if self.direction != Direction::Positive {
-            context.painter.line_segment(
+            context.line(
                 [
                     context.cell_rect(0, 0).left_top(),
                     context
                         .cell_rect(context.height - 1, context.width - 1)
                         .right_bottom(),
                 ],
-                egui::Stroke::new(1f32, context.color),
+                1f32,
             );
         }
         if self.direction != Direction::Negative {
-            context.painter.line_segment(
+            context.line(
                 [
                     context.cell_rect(0, context.width - 1).right_top(),
                     context.cell_rect(context.height - 1, 0).left_bottom(),
                 ],
-                egui::Stroke::new(1f32, context.color),
+                1f32,
             );
         }
     }
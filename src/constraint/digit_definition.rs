@@ -1,11 +1,12 @@
 use z3::ast::Ast;
 use crate::constraint::Constraint;
 use crate::sudoku::SudokuContext;
+use crate::z3_helper::Assertions;
 
 pub struct DigitDefinitionConstraint;
 
 impl Constraint for DigitDefinitionConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         for cell in context.all_cells() {
             solver.assert(
                 context.bools().alloc(z3::ast::Bool::or(
@@ -17,4 +18,21 @@ impl Constraint for DigitDefinitionConstraint {
             );
         }
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        for row in 0..context.height() {
+            for col in 0..context.width() {
+                let cell = context.get_cell(row, col);
+                let digits = context
+                    .digits_range()
+                    .map(|digit| context.cell_is(row, col, digit))
+                    .collect::<Vec<_>>();
+                solver.assert(context.exactly_one(&digits));
+                for (digit, is_digit) in context.digits_range().zip(digits) {
+                    let cell_is_digit = cell._eq(context.const_int(digit as i32));
+                    solver.assert(context.bools().alloc(is_digit._eq(&cell_is_digit)));
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,78 @@
+use eframe::egui::{Context, Ui};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku::{Cell, SudokuContext, SUDOKU_SIZE};
+use macros::DynClone;
+use z3::ast::Ast;
+use crate::z3_helper::Assertions;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct DisjointGroupsConstraint;
+
+impl DisjointGroupsConstraint {
+    // The cells at the same position within each box.
+    fn groups() -> impl Iterator<Item = Vec<Cell>> {
+        (0..3).flat_map(|dx| {
+            (0..3).map(move |dy| {
+                (0..3)
+                    .flat_map(|x| (0..3).map(move |y| Cell::new(x * 3 + dx, y * 3 + dy)))
+                    .collect()
+            })
+        })
+    }
+}
+
+impl Constraint for DisjointGroupsConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        assert!(context.width() == SUDOKU_SIZE && context.height() == SUDOKU_SIZE);
+        for group in Self::groups() {
+            solver.assert(
+                context.bools().alloc(z3::ast::Int::distinct(
+                    context.ctx(),
+                    &group
+                        .iter()
+                        .map(|cell| context.get_cell(cell.row, cell.col))
+                        .collect::<Vec<_>>(),
+                )),
+            );
+        }
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        assert!(context.width() == SUDOKU_SIZE && context.height() == SUDOKU_SIZE);
+        for group in Self::groups() {
+            solver.assert(context.one_hot_distinct(&group));
+        }
+    }
+}
+
+impl ConfigurableConstraint for DisjointGroupsConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
+        None
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Disjoint Groups"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, GRID, MIRACLE_GRID};
+
+    #[test]
+    fn test_disjoint_groups() {
+        assert!(satisfies(&[&DisjointGroupsConstraint], MIRACLE_GRID));
+        // R1C4 and R4C7 are both 9s.
+        assert!(!satisfies(&[&DisjointGroupsConstraint], GRID));
+    }
+}
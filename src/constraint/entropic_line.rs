@@ -3,10 +3,10 @@ use crate::constraint::{renban, ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
 
 #[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
@@ -15,7 +15,7 @@ pub struct EntropicLineConstraint {
 }
 
 impl Constraint for EntropicLineConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         let divisor = context.const_int(
             ((*context.digits_range().end() - *context.digits_range().start() + 2) / 3) as i32,
         );
@@ -40,7 +40,9 @@ impl Constraint for EntropicLineConstraint {
 }
 
 impl ConfigurableConstraint for EntropicLineConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -0,0 +1,105 @@
+use eframe::egui;
+use z3::ast::Ast;
+use macros::DynClone;
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::{SudokuContext, SUDOKU_SIZE};
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+
+// The top left cell of each of the four Windoku boxes.
+const WINDOKU_CORNERS: [(usize, usize); 4] = [(1, 1), (1, 5), (5, 1), (5, 5)];
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct ExtraRegionConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for ExtraRegionConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let cells = self
+            .cells
+            .iter()
+            .map(|cell| context.get_cell(cell.row, cell.col))
+            .collect::<Vec<_>>();
+        solver.assert(
+            context
+                .bools()
+                .alloc(z3::ast::Int::distinct(context.ctx(), &cells)),
+        );
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        solver.assert(context.one_hot_distinct(&self.cells));
+    }
+}
+
+impl ConfigurableConstraint for ExtraRegionConstraint {
+    fn configure(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Preset");
+            for (i, (row, col)) in WINDOKU_CORNERS.into_iter().enumerate() {
+                if ui.button(format!("Windoku {}", i + 1)).clicked() {
+                    changed = true;
+                    self.cells = (row..row + 3)
+                        .flat_map(|row| (col..col + 3).map(move |col| sudoku::Cell::new(row, col)))
+                        .collect();
+                }
+            }
+            if ui.button("Centre dots").clicked() {
+                changed = true;
+                self.cells = (0..3)
+                    .flat_map(|x| (0..3).map(move |y| sudoku::Cell::new(x * 3 + 1, y * 3 + 1)))
+                    .collect();
+            }
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        SUDOKU_SIZE
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() == SUDOKU_SIZE
+    }
+
+    fn name(&self) -> &'static str {
+        "Extra Region"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        for cell in &self.cells {
+            context.shade_cell(cell.row, cell.col);
+        }
+    }
+
+    fn draw_depth(&self) -> i32 {
+        20
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, GRID, MIRACLE_GRID};
+
+    #[test]
+    fn test_extra_region() {
+        // The last Windoku box, from R6C6 to R8C8.
+        let constraint = ExtraRegionConstraint {
+            cells: (5..8)
+                .flat_map(|row| (5..8).map(move |col| sudoku::Cell::new(row, col)))
+                .collect(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        // R6C6 and R8C8 are both 7s.
+        assert!(!satisfies(&[&constraint], MIRACLE_GRID));
+    }
+}
@@ -1,7 +1,9 @@
 use z3::ast::Ast;
 use crate::constraint::Constraint;
 use crate::sudoku::SudokuContext;
+use crate::z3_helper::Assertions;
 
+#[derive(Clone)]
 pub struct GivenDigitConstraint {
     pub row: usize,
     pub col: usize,
@@ -9,7 +11,7 @@ pub struct GivenDigitConstraint {
 }
 
 impl Constraint for GivenDigitConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         solver.assert(
             context.bools().alloc(
                 context
@@ -18,4 +20,8 @@ impl Constraint for GivenDigitConstraint {
             ),
         );
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        solver.assert(context.cell_is(self.row, self.col, self.value as usize));
+    }
 }
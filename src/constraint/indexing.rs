@@ -0,0 +1,213 @@
+use crate::constraint::x_sum::{
+    direction_combo, draw_number_outside_grid, iter_cells_in_dir, Direction,
+};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct NumberedRoomConstraint {
+    digit: String,
+    direction: Direction,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for NumberedRoomConstraint {
+    fn default() -> Self {
+        Self {
+            digit: "1".to_owned(),
+            direction: Direction::Horizontal,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for NumberedRoomConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let start_pos = self.cells[0];
+        let start_cell = context.get_cell(start_pos.row, start_pos.col);
+        let digit = context.const_int(self.digit.parse().unwrap());
+
+        // If the first digit is X, the Xth digit from the edge is the clue.
+        iter_cells_in_dir(start_pos, self.direction, context, |i, cell| {
+            solver.assert(
+                context.bools().alloc(
+                    start_cell._eq(context.const_int(i as i32 + 1)).implies(
+                        context
+                            .bools()
+                            .alloc(context.get_cell(cell.row, cell.col)._eq(digit)),
+                    ),
+                ),
+            );
+        });
+    }
+}
+
+impl ConfigurableConstraint for NumberedRoomConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.label(
+            "Place the numbered room on the perimeter of the grid with the appropriate direction.",
+        );
+        ui.horizontal(|ui| {
+            ui.label("Digit");
+            changed |= ui.text_edit_singleline(&mut self.digit).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Direction");
+            changed |= direction_combo("choose_direction", &mut self.direction, ui);
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        1
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.cells.is_empty()
+            && matches!(
+                self.digit.parse::<usize>(),
+                Ok(digit) if (1..=sudoku::SUDOKU_SIZE).contains(&digit)
+            )
+    }
+
+    fn name(&self) -> &'static str {
+        "Numbered Room"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_number_outside_grid(&self.cells, &self.digit, self.direction, context);
+    }
+}
+
+// The general form of 1-5-9 style puzzles. Each indexing cell says where in its row (or column)
+// its own column (or row) number goes: if R4C5 indexes its row and contains 7, then R4C7 is a 5.
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct IndexingConstraint {
+    direction: Direction,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for IndexingConstraint {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Horizontal,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for IndexingConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        for index_pos in &self.cells {
+            let index_cell = context.get_cell(index_pos.row, index_pos.col);
+            let (targets, indexed_digit) = match self.direction {
+                Direction::Horizontal => (
+                    (0..context.width())
+                        .map(|col| sudoku::Cell::new(index_pos.row, col))
+                        .collect::<Vec<_>>(),
+                    index_pos.col + 1,
+                ),
+                Direction::Vertical => (
+                    (0..context.height())
+                        .map(|row| sudoku::Cell::new(row, index_pos.col))
+                        .collect::<Vec<_>>(),
+                    index_pos.row + 1,
+                ),
+            };
+            let indexed_digit = context.const_int(indexed_digit as i32);
+
+            for (i, target) in targets.into_iter().enumerate() {
+                solver.assert(
+                    context.bools().alloc(
+                        index_cell._eq(context.const_int(i as i32 + 1)).implies(
+                            context
+                                .bools()
+                                .alloc(context.get_cell(target.row, target.col)._eq(indexed_digit)),
+                        ),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl ConfigurableConstraint for IndexingConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.label("Each indexing cell gives the position in its row or column of its own column or row number.");
+        ui.horizontal(|ui| {
+            ui.label("Indexes");
+            changed |= direction_combo("choose_indexing_direction", &mut self.direction, ui);
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.cells.is_empty()
+    }
+
+    fn name(&self) -> &'static str {
+        "Indexing Cells"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        if self.cells.is_empty() {
+            context.default_draw();
+            return;
+        }
+
+        for cell in &self.cells {
+            context.shade_cell(cell.row, cell.col);
+        }
+    }
+
+    fn draw_depth(&self) -> i32 {
+        20
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_numbered_room() {
+        // The first row is 483921657, so the 4th digit from the left is the clue.
+        let constraint = NumberedRoomConstraint {
+            digit: "9".to_owned(),
+            direction: Direction::Horizontal,
+            cells: vec![sudoku::Cell::new(0, 0)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '9', '7')));
+    }
+
+    #[test]
+    fn test_indexing() {
+        // R2C1 is a 9 and R2C9 is a 1.
+        let constraint = IndexingConstraint {
+            direction: Direction::Horizontal,
+            cells: vec![sudoku::Cell::new(1, 0)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '1', '2')));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::constraint::kropki::draw_kropki_dot;
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use ahash::AHashSet;
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+
+// Draws a chevron with its tip at `tip`, pointing in `direction`, which must be normalized.
+pub fn draw_chevron(
+    context: &SudokuDrawContext,
+    tip: egui::Pos2,
+    direction: egui::Vec2,
+    size: f32,
+) {
+    let back = tip - direction * size;
+    let side = direction.rot90() * size;
+    context.line([back + side, tip], 1.5);
+    context.line([back - side, tip], 1.5);
+}
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct GreaterThanConstraint {
+    // The first cell is greater than the second.
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for GreaterThanConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let greater = context.get_cell(self.cells[0].row, self.cells[0].col);
+        let smaller = context.get_cell(self.cells[1].row, self.cells[1].col);
+        solver.assert(context.bools().alloc(greater.gt(smaller)));
+    }
+}
+
+impl ConfigurableConstraint for GreaterThanConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        ui.label("Select the greater cell first.");
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        2
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() == 2
+    }
+
+    fn name(&self) -> &'static str {
+        "Greater Than"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_kropki_dot(&self.cells, context, |center, radius| {
+            let greater = context.cell_rect(self.cells[0].row, self.cells[0].col);
+            let smaller = context.cell_rect(self.cells[1].row, self.cells[1].col);
+            let direction = (smaller.center() - greater.center()).normalized();
+            draw_chevron(
+                context,
+                center + direction * radius,
+                direction,
+                radius * 2.0,
+            );
+        });
+    }
+}
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct FortressConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for FortressConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let shaded: AHashSet<_> = self.cells.iter().copied().collect();
+        for &cell in &self.cells {
+            let value = context.get_cell(cell.row, cell.col);
+            for neighbour in context.orthogonal_neighbours(cell) {
+                if !shaded.contains(&neighbour) {
+                    let neighbour = context.get_cell(neighbour.row, neighbour.col);
+                    solver.assert(context.bools().alloc(value.gt(neighbour)));
+                }
+            }
+        }
+    }
+}
+
+impl ConfigurableConstraint for FortressConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.cells.is_empty()
+    }
+
+    fn name(&self) -> &'static str {
+        "Fortress"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        for cell in &self.cells {
+            context.shade_cell(cell.row, cell.col);
+        }
+    }
+
+    fn draw_depth(&self) -> i32 {
+        20
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_greater_than() {
+        // R1C2 is an 8 and R1C1 is a 4.
+        let constraint = GreaterThanConstraint {
+            cells: vec![sudoku::Cell::new(0, 1), sudoku::Cell::new(0, 0)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '3', '8')));
+    }
+
+    #[test]
+    fn test_fortress() {
+        // R3C4 and R3C5 are an 8 and a 7, next to a 6 in R3C6 and smaller digits elsewhere. The
+        // shaded cells don't have to be greater than each other.
+        let constraint = FortressConstraint {
+            cells: vec![sudoku::Cell::new(2, 3), sudoku::Cell::new(2, 4)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '6', '7')));
+    }
+}
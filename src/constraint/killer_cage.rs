@@ -5,51 +5,93 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::{sudoku, ui};
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 
-#[derive(DynClone)]
+#[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
 pub struct KillerCageConstraint {
     cells: Vec<sudoku::Cell>,
+    // Empty if the total is unknown, otherwise a number or a cell such as "r1c1" whose digit gives
+    // the total.
     total: String,
+    allow_repeats: bool,
 }
 
-impl Default for KillerCageConstraint {
-    fn default() -> Self {
-        Self {
-            cells: Vec::new(),
-            total: "0".to_owned(),
-        }
-    }
+#[derive(Debug, PartialEq)]
+enum Total {
+    Unknown,
+    Value(i32),
+    Cell(sudoku::Cell),
 }
 
 impl Constraint for KillerCageConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
-        let total: i32 = self.total.parse().unwrap();
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        if !self.allow_repeats {
+            solver.assert(
+                context.bools().alloc(z3::ast::Int::distinct(
+                    context.ctx(),
+                    &self
+                        .cells
+                        .iter()
+                        .map(|cell| context.get_cell(cell.row, cell.col))
+                        .collect::<Vec<_>>(),
+                )),
+            );
+        }
+        self.assert_total(solver, context);
+    }
 
-        let cells = self
-            .cells
-            .iter()
-            .map(|cell| context.get_cell(cell.row, cell.col))
-            .collect::<Vec<_>>();
-        solver.assert(
-            context
-                .bools()
-                .alloc(z3::ast::Int::distinct(context.ctx(), &cells)),
-        );
-        solver.assert(
-            context
-                .bools()
-                .alloc(z3::ast::Int::add(context.ctx(), &cells)._eq(context.const_int(total))),
-        );
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        if !self.allow_repeats {
+            solver.assert(context.one_hot_distinct(&self.cells));
+        }
+        // The sum is still taken over the integer cells, which are tied to the digit booleans.
+        self.assert_total(solver, context);
+
+        // With a fixed total and no repeats, digits that aren't part of any way of making the
+        // total can be ruled out for every cell.
+        let total = match self.parse_total().unwrap() {
+            Total::Value(total) if !self.allow_repeats => total,
+            _ => return,
+        };
+        let digits = context.digits_range().collect::<Vec<_>>();
+        let possible = (0u32..1 << digits.len())
+            .filter(|mask| {
+                mask.count_ones() as usize == self.cells.len()
+                    && digits
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| mask & 1 << i != 0)
+                        .map(|(_, &digit)| digit as i32)
+                        .sum::<i32>()
+                        == total
+            })
+            .fold(0, |possible, mask| possible | mask);
+        for (i, &digit) in digits.iter().enumerate() {
+            if possible & 1 << i == 0 {
+                for cell in &self.cells {
+                    solver.assert(
+                        context
+                            .bools()
+                            .alloc(context.cell_is(cell.row, cell.col, digit).not()),
+                    );
+                }
+            }
+        }
     }
 }
 
 impl ConfigurableConstraint for KillerCageConstraint {
-    fn configure(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) {
+    fn configure(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
         ui.horizontal(|ui| {
             ui.label("Total");
-            ui.text_edit_singleline(&mut self.total);
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut self.total).hint_text("Unknown"))
+                .changed();
         });
+        changed |= ui.checkbox(&mut self.allow_repeats, "Allow repeated digits").changed();
+        changed
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
@@ -57,7 +99,7 @@ impl ConfigurableConstraint for KillerCageConstraint {
     }
 
     fn is_valid(&self) -> bool {
-        !self.cells.is_empty() && self.total.parse::<i32>().is_ok()
+        !self.cells.is_empty() && self.parse_total().is_some()
     }
 
     fn name(&self) -> &'static str {
@@ -66,13 +108,12 @@ impl ConfigurableConstraint for KillerCageConstraint {
 
     fn draw(&self, context: &SudokuDrawContext) {
         if let Some(top_left_cell) = self.cells.iter().min_by_key(|cell| (cell.row, cell.col)) {
-            context.painter.text(
+            context.text(
                 context.cell_rect(top_left_cell.row, top_left_cell.col).min
                     + egui::Vec2::splat(4.0),
                 egui::Align2::LEFT_TOP,
                 &self.total,
-                context.get_font(0.2),
-                context.color,
+                context.text_size(0.2),
             );
         }
 
@@ -94,23 +135,21 @@ impl ConfigurableConstraint for KillerCageConstraint {
             if up {
                 if right && !up_right {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         cage_rect.right_top(),
                         egui::Pos2::new(cell_rect.right(), cage_rect.top()),
-                        context.color,
                     );
                 }
                 if left && !up_left {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         egui::Pos2::new(cell_rect.left(), cage_rect.top()),
                         cage_rect.left_top(),
-                        context.color,
                     );
                 }
             } else {
                 Self::draw_dashed_line(
-                    context.painter,
+                    context,
                     egui::Pos2::new(
                         if left {
                             cell_rect.left()
@@ -127,30 +166,27 @@ impl ConfigurableConstraint for KillerCageConstraint {
                         },
                         cage_rect.top(),
                     ),
-                    context.color,
                 );
             }
 
             if down {
                 if right && !down_right {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         cage_rect.right_bottom(),
                         egui::Pos2::new(cell_rect.right(), cage_rect.bottom()),
-                        context.color,
                     );
                 }
                 if left && !down_left {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         egui::Pos2::new(cell_rect.left(), cage_rect.bottom()),
                         cage_rect.left_bottom(),
-                        context.color,
                     );
                 }
             } else {
                 Self::draw_dashed_line(
-                    context.painter,
+                    context,
                     egui::Pos2::new(
                         if left {
                             cell_rect.left()
@@ -167,30 +203,27 @@ impl ConfigurableConstraint for KillerCageConstraint {
                         },
                         cage_rect.bottom(),
                     ),
-                    context.color,
                 );
             }
 
             if left {
                 if up && !up_left {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         egui::Pos2::new(cage_rect.left(), cell_rect.top()),
                         cage_rect.left_top(),
-                        context.color,
                     );
                 }
                 if down && !down_left {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         cage_rect.left_bottom(),
                         egui::Pos2::new(cage_rect.left(), cell_rect.bottom()),
-                        context.color,
                     );
                 }
             } else {
                 Self::draw_dashed_line(
-                    context.painter,
+                    context,
                     egui::Pos2::new(
                         cage_rect.left(),
                         if up { cell_rect.top() } else { cage_rect.top() },
@@ -203,30 +236,27 @@ impl ConfigurableConstraint for KillerCageConstraint {
                             cage_rect.bottom()
                         },
                     ),
-                    context.color,
                 );
             }
 
             if right {
                 if up && !up_right {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         egui::Pos2::new(cage_rect.right(), cell_rect.top()),
                         cage_rect.right_top(),
-                        context.color,
                     );
                 }
                 if down && !down_right {
                     Self::draw_dashed_line(
-                        context.painter,
+                        context,
                         cage_rect.right_bottom(),
                         egui::Pos2::new(cage_rect.right(), cell_rect.bottom()),
-                        context.color,
                     );
                 }
             } else {
                 Self::draw_dashed_line(
-                    context.painter,
+                    context,
                     egui::Pos2::new(
                         cage_rect.right(),
                         if up { cell_rect.top() } else { cage_rect.top() },
@@ -239,7 +269,6 @@ impl ConfigurableConstraint for KillerCageConstraint {
                             cage_rect.bottom()
                         },
                     ),
-                    context.color,
                 );
             }
         }
@@ -251,22 +280,84 @@ impl ConfigurableConstraint for KillerCageConstraint {
 }
 
 impl KillerCageConstraint {
-    fn draw_dashed_line(
-        painter: &egui::Painter,
-        from: egui::Pos2,
-        to: egui::Pos2,
-        color: egui::Color32,
-    ) {
-        let total_len = from.distance(to);
-        let dir = (to - from).normalized();
+    pub fn new(cells: Vec<sudoku::Cell>, total: i32) -> Self {
+        Self {
+            cells,
+            total: total.to_string(),
+            allow_repeats: false,
+        }
+    }
 
-        let mut dist = 0.0;
-        while dist < total_len {
-            painter.line_segment(
-                [from + dir * dist, from + dir * (dist + 2.0)],
-                egui::Stroke::new(1.0, color),
-            );
-            dist += 4.0;
+    fn assert_total(&self, solver: &Assertions, context: &SudokuContext) {
+        let total = match self.parse_total().unwrap() {
+            Total::Unknown => return,
+            Total::Value(total) => context.const_int(total),
+            Total::Cell(cell) => context.get_cell(cell.row, cell.col),
+        };
+        solver.assert(
+            context.bools().alloc(
+                z3::ast::Int::add(
+                    context.ctx(),
+                    &self
+                        .cells
+                        .iter()
+                        .map(|cell| context.get_cell(cell.row, cell.col))
+                        .collect::<Vec<_>>(),
+                )
+                ._eq(total),
+            ),
+        );
+    }
+
+    fn parse_total(&self) -> Option<Total> {
+        let total = self.total.trim();
+        if total.is_empty() {
+            return Some(Total::Unknown);
+        }
+        if let Ok(total) = total.parse() {
+            return Some(Total::Value(total));
+        }
+
+        // A cell reference such as "r1c1".
+        total.parse().ok().map(Total::Cell)
+    }
+
+    fn draw_dashed_line(context: &SudokuDrawContext, from: egui::Pos2, to: egui::Pos2) {
+        context.dashed_line([from, to], 1.0, 2.0, 2.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(total: &str) -> Option<Total> {
+        KillerCageConstraint {
+            total: total.to_owned(),
+            ..Default::default()
         }
+        .parse_total()
+    }
+
+    #[test]
+    fn test_parse_total() {
+        assert_eq!(parse(""), Some(Total::Unknown));
+        assert_eq!(parse("  "), Some(Total::Unknown));
+        assert_eq!(parse("12"), Some(Total::Value(12)));
+        assert_eq!(parse(" 12 "), Some(Total::Value(12)));
+        assert_eq!(parse("r3c4"), Some(Total::Cell(sudoku::Cell::new(2, 3))));
+        assert_eq!(parse("R9C9"), Some(Total::Cell(sudoku::Cell::new(8, 8))));
+    }
+
+    #[test]
+    fn test_parse_total_rejects_invalid() {
+        // Out of range references must be rejected rather than indexing outside the grid.
+        assert_eq!(parse("R10C1"), None);
+        assert_eq!(parse("r1c10"), None);
+        assert_eq!(parse("r0c1"), None);
+        assert_eq!(parse("abc"), None);
+        assert_eq!(parse("r3"), None);
+        assert_eq!(parse("rxcy"), None);
+        assert_eq!(parse("12a"), None);
     }
 }
@@ -2,15 +2,15 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 use ahash::AHashSet;
 use eframe::egui;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use std::ops::{Add, Mul};
 use z3::ast::Ast;
-use z3::Solver;
 
-fn draw_kropki_dot(
+pub fn draw_kropki_dot(
     cells: &[sudoku::Cell],
     context: &SudokuDrawContext,
     draw_dot: impl FnOnce(egui::Pos2, f32),
@@ -33,9 +33,71 @@ fn draw_kropki_dot(
     draw_dot(rect_a.union(rect_b).center(), rect_a.width() * 0.1);
 }
 
-fn white_kropki_constraint<'a>(
+const DEFAULT_DIFFERENCE: i32 = 1;
+const DEFAULT_RATIO: i32 = 2;
+
+// Parses a dot's difference or ratio, so that e.g. "01" and " 1" are the same value as "1".
+fn parse_value(value: &str, min: i32) -> Option<i32> {
+    value.trim().parse().ok().filter(|&value| value >= min)
+}
+
+// Dots with a non-standard value are drawn larger, with the value written inside.
+fn draw_labelled_dot(
+    cells: &[sudoku::Cell],
+    context: &SudokuDrawContext,
+    label: Option<&str>,
+    draw_dot: impl FnOnce(egui::Pos2, f32),
+    text_color: egui::Color32,
+) {
+    draw_kropki_dot(cells, context, |center, radius| match label {
+        Some(label) => {
+            let radius = radius * 1.6;
+            draw_dot(center, radius);
+            context.text_with_color(
+                center,
+                egui::Align2::CENTER_CENTER,
+                label,
+                radius * 1.4,
+                text_color,
+            );
+        }
+        None => draw_dot(center, radius),
+    });
+}
+
+// The one-hot form of a dot: a digit in either cell of the pair is only allowed when the other
+// cell holds a digit that is related to it.
+fn one_hot_pair(
+    solver: &Assertions,
+    context: &SudokuContext,
+    a: sudoku::Cell,
+    b: sudoku::Cell,
+    related: impl Fn(usize, usize) -> bool,
+) {
+    for (cell, other) in [(a, b), (b, a)] {
+        for digit in context.digits_range() {
+            let partners = context
+                .digits_range()
+                .filter(|&partner| related(digit, partner))
+                .map(|partner| context.cell_is(other.row, other.col, partner))
+                .collect::<Vec<_>>();
+            solver.assert(
+                context.bools().alloc(
+                    context.cell_is(cell.row, cell.col, digit).implies(
+                        context
+                            .bools()
+                            .alloc(z3::ast::Bool::or(context.ctx(), &partners)),
+                    ),
+                ),
+            );
+        }
+    }
+}
+
+pub fn white_kropki_constraint<'a>(
     a: sudoku::Cell,
     b: sudoku::Cell,
+    difference: i32,
     context: &'a SudokuContext<'a>,
 ) -> z3::ast::Bool<'a> {
     let a = context.get_cell(a.row, a.col);
@@ -45,32 +107,63 @@ fn white_kropki_constraint<'a>(
         &[
             context
                 .bools()
-                .alloc(a._eq(context.ints().alloc(b.add(context.const_int(1))))),
+                .alloc(a._eq(context.ints().alloc(b.add(context.const_int(difference))))),
             context
                 .bools()
-                .alloc(b._eq(context.ints().alloc(a.add(context.const_int(1))))),
+                .alloc(b._eq(context.ints().alloc(a.add(context.const_int(difference))))),
         ],
     )
 }
 
-#[derive(Default, DynClone)]
+#[derive(DynClone)]
 #[dyn_clone(Constraint + Send)]
 pub struct WhiteKropkiConstraint {
     cells: Vec<sudoku::Cell>,
+    difference: String,
+}
+
+impl Default for WhiteKropkiConstraint {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            difference: DEFAULT_DIFFERENCE.to_string(),
+        }
+    }
+}
+
+impl WhiteKropkiConstraint {
+    fn difference(&self) -> Option<i32> {
+        parse_value(&self.difference, 1)
+    }
 }
 
 impl Constraint for WhiteKropkiConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         solver.assert(context.bools().alloc(white_kropki_constraint(
             self.cells[0],
             self.cells[1],
+            self.difference().unwrap(),
             context,
         )));
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let difference = self.difference().unwrap() as usize;
+        one_hot_pair(solver, context, self.cells[0], self.cells[1], |a, b| {
+            a.abs_diff(b) == difference
+        });
+    }
 }
 
 impl ConfigurableConstraint for WhiteKropkiConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Difference");
+            changed |= ui.text_edit_singleline(&mut self.difference).changed();
+        });
+        changed
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -81,7 +174,7 @@ impl ConfigurableConstraint for WhiteKropkiConstraint {
     }
 
     fn is_valid(&self) -> bool {
-        self.cells.len() == 2
+        self.cells.len() == 2 && self.difference().is_some()
     }
 
     fn name(&self) -> &'static str {
@@ -89,17 +182,24 @@ impl ConfigurableConstraint for WhiteKropkiConstraint {
     }
 
     fn draw(&self, context: &SudokuDrawContext) {
-        draw_kropki_dot(&self.cells, context, |center, radius| {
-            context
-                .painter
-                .circle_stroke(center, radius, egui::Stroke::new(1f32, context.color));
-        });
+        let label = self
+            .difference()
+            .filter(|&difference| difference != DEFAULT_DIFFERENCE)
+            .map(|difference| difference.to_string());
+        draw_labelled_dot(
+            &self.cells,
+            context,
+            label.as_deref(),
+            |center, radius| context.circle_stroke(center, radius, 1f32),
+            context.color,
+        );
     }
 }
 
 fn black_kropki_constraint<'a>(
     a: sudoku::Cell,
     b: sudoku::Cell,
+    ratio: i32,
     context: &'a SudokuContext<'a>,
 ) -> z3::ast::Bool<'a> {
     let a = context.get_cell(a.row, a.col);
@@ -109,32 +209,63 @@ fn black_kropki_constraint<'a>(
         &[
             context
                 .bools()
-                .alloc(a._eq(context.ints().alloc(b.mul(context.const_int(2))))),
+                .alloc(a._eq(context.ints().alloc(b.mul(context.const_int(ratio))))),
             context
                 .bools()
-                .alloc(b._eq(context.ints().alloc(a.mul(context.const_int(2))))),
+                .alloc(b._eq(context.ints().alloc(a.mul(context.const_int(ratio))))),
         ],
     )
 }
 
-#[derive(Default, DynClone)]
+#[derive(DynClone)]
 #[dyn_clone(Constraint + Send)]
 pub struct BlackKropkiConstraint {
     cells: Vec<sudoku::Cell>,
+    ratio: String,
+}
+
+impl Default for BlackKropkiConstraint {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            ratio: DEFAULT_RATIO.to_string(),
+        }
+    }
+}
+
+impl BlackKropkiConstraint {
+    fn ratio(&self) -> Option<i32> {
+        parse_value(&self.ratio, 2)
+    }
 }
 
 impl Constraint for BlackKropkiConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         solver.assert(context.bools().alloc(black_kropki_constraint(
             self.cells[0],
             self.cells[1],
+            self.ratio().unwrap(),
             context,
         )));
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let ratio = self.ratio().unwrap() as usize;
+        one_hot_pair(solver, context, self.cells[0], self.cells[1], |a, b| {
+            a == b * ratio || b == a * ratio
+        });
+    }
 }
 
 impl ConfigurableConstraint for BlackKropkiConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Ratio");
+            changed |= ui.text_edit_singleline(&mut self.ratio).changed();
+        });
+        changed
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -145,7 +276,7 @@ impl ConfigurableConstraint for BlackKropkiConstraint {
     }
 
     fn is_valid(&self) -> bool {
-        self.cells.len() == 2
+        self.cells.len() == 2 && self.ratio().is_some()
     }
 
     fn name(&self) -> &'static str {
@@ -153,50 +284,79 @@ impl ConfigurableConstraint for BlackKropkiConstraint {
     }
 
     fn draw(&self, context: &SudokuDrawContext) {
-        draw_kropki_dot(&self.cells, context, |center, radius| {
-            context.painter.circle_filled(center, radius, context.color);
-        });
+        let label = self
+            .ratio()
+            .filter(|&ratio| ratio != DEFAULT_RATIO)
+            .map(|ratio| ratio.to_string());
+        draw_labelled_dot(
+            &self.cells,
+            context,
+            label.as_deref(),
+            |center, radius| context.circle_filled(center, radius),
+            egui::Color32::WHITE,
+        );
     }
 }
 
-fn find_kropki_dots(context: &SudokuContext) -> AHashSet<(sudoku::Cell, sudoku::Cell)> {
+// The two cells of a marker between orthogonally adjacent cells, ordered top to bottom or left to
+// right so that markers can be looked up regardless of the order the cells were selected in.
+pub fn ordered_pair(cells: &[sudoku::Cell]) -> Option<(sudoku::Cell, sudoku::Cell)> {
+    let first = cells[0];
+    let second = cells[1];
+    match (first.row == second.row, first.col == second.col) {
+        (true, true) => {
+            unreachable!("The ui should prevent two of the same cell in a constraint")
+        }
+        (true, false) => Some((
+            sudoku::Cell::new(first.row, first.col.min(second.col)),
+            sudoku::Cell::new(second.row, first.col.max(second.col)),
+        )),
+        (false, true) => Some((
+            sudoku::Cell::new(first.row.min(second.row), first.col),
+            sudoku::Cell::new(first.row.max(second.row), second.col),
+        )),
+        (false, false) => None,
+    }
+}
+
+// Finds the pairs of cells that are exempt from a negative constraint. A dot of the other colour
+// always exempts a pair, but a dot of the same colour only does if it has the same value, so that
+// e.g. a white dot with difference 3 doesn't hide a pair of consecutive digits.
+fn find_kropki_dots(
+    context: &SudokuContext,
+    difference: Option<i32>,
+    ratio: Option<i32>,
+) -> AHashSet<(sudoku::Cell, sudoku::Cell)> {
     context
         .constraints()
         .iter()
         .filter_map(|constraint| {
             let cells = if let Some(white) = constraint.downcast::<WhiteKropkiConstraint>() {
+                if difference.is_some() && difference != white.difference() {
+                    return None;
+                }
                 &white.cells
             } else if let Some(black) = constraint.downcast::<BlackKropkiConstraint>() {
+                if ratio.is_some() && ratio != black.ratio() {
+                    return None;
+                }
                 &black.cells
             } else {
                 return None;
             };
-            let first = cells[0];
-            let second = cells[1];
-            match (first.row == second.row, first.col == second.col) {
-                (true, true) => {
-                    unreachable!("The ui should prevent two of the same cell in a constraint")
-                }
-                (true, false) => Some((
-                    sudoku::Cell::new(first.row, first.col.min(second.col)),
-                    sudoku::Cell::new(second.row, first.col.max(second.col)),
-                )),
-                (false, true) => Some((
-                    sudoku::Cell::new(first.row.min(second.row), first.col),
-                    sudoku::Cell::new(first.row.max(second.row), second.col),
-                )),
-                (false, false) => None,
-            }
+            ordered_pair(cells)
         })
         .collect()
 }
 
-fn negative_constraint(
-    solver: &Solver,
+// Asserts that the constraint doesn't hold between any orthogonally adjacent pair of cells that
+// isn't in `dots`.
+pub fn negative_constraint(
+    solver: &Assertions,
     context: &SudokuContext,
+    dots: &AHashSet<(sudoku::Cell, sudoku::Cell)>,
     constraint: impl for<'a> Fn(sudoku::Cell, sudoku::Cell, &'a SudokuContext<'a>) -> z3::ast::Bool<'a>,
 ) {
-    let dots = find_kropki_dots(context);
     for row in 0..context.height() {
         for col in 0..context.width() - 1 {
             let a = sudoku::Cell::new(row, col);
@@ -217,25 +377,58 @@ fn negative_constraint(
     }
 }
 
-#[derive(Default, DynClone)]
+#[derive(DynClone)]
 #[dyn_clone(Constraint + Send)]
-pub struct NegativeWhiteKropkiConstraint;
+pub struct NegativeWhiteKropkiConstraint {
+    difference: String,
+}
+
+impl Default for NegativeWhiteKropkiConstraint {
+    fn default() -> Self {
+        Self {
+            difference: DEFAULT_DIFFERENCE.to_string(),
+        }
+    }
+}
+
+impl NegativeWhiteKropkiConstraint {
+    fn difference(&self) -> Option<i32> {
+        parse_value(&self.difference, 1)
+    }
+}
 
 impl Constraint for NegativeWhiteKropkiConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
-        negative_constraint(solver, context, white_kropki_constraint);
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let difference = self.difference().unwrap();
+        negative_constraint(
+            solver,
+            context,
+            &find_kropki_dots(context, Some(difference), None),
+            |a, b, context| white_kropki_constraint(a, b, difference, context),
+        );
+    }
+
+    fn uses_other_constraints(&self) -> bool {
+        true
     }
 }
 
 impl ConfigurableConstraint for NegativeWhiteKropkiConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Difference");
+            changed |= ui.text_edit_singleline(&mut self.difference).changed();
+        });
+        changed
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         None
     }
 
     fn is_valid(&self) -> bool {
-        true
+        self.difference().is_some()
     }
 
     fn name(&self) -> &'static str {
@@ -243,28 +436,113 @@ impl ConfigurableConstraint for NegativeWhiteKropkiConstraint {
     }
 }
 
-#[derive(Default, DynClone)]
+#[derive(DynClone)]
 #[dyn_clone(Constraint + Send)]
-pub struct NegativeBlackKropkiConstraint;
+pub struct NegativeBlackKropkiConstraint {
+    ratio: String,
+}
+
+impl Default for NegativeBlackKropkiConstraint {
+    fn default() -> Self {
+        Self {
+            ratio: DEFAULT_RATIO.to_string(),
+        }
+    }
+}
+
+impl NegativeBlackKropkiConstraint {
+    fn ratio(&self) -> Option<i32> {
+        parse_value(&self.ratio, 2)
+    }
+}
 
 impl Constraint for NegativeBlackKropkiConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
-        negative_constraint(solver, context, black_kropki_constraint);
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let ratio = self.ratio().unwrap();
+        negative_constraint(
+            solver,
+            context,
+            &find_kropki_dots(context, None, Some(ratio)),
+            |a, b, context| black_kropki_constraint(a, b, ratio, context),
+        );
+    }
+
+    fn uses_other_constraints(&self) -> bool {
+        true
     }
 }
 
 impl ConfigurableConstraint for NegativeBlackKropkiConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Ratio");
+            changed |= ui.text_edit_singleline(&mut self.ratio).changed();
+        });
+        changed
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         None
     }
 
     fn is_valid(&self) -> bool {
-        true
+        self.ratio().is_some()
     }
 
     fn name(&self) -> &'static str {
         "Black Kropki Dots (Negative Constraint)"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{adjacent_pairs, digit_at, satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_white_kropki_difference() {
+        // R1C8 and R2C8 are a 5 and a 2.
+        let constraint = WhiteKropkiConstraint {
+            cells: vec![sudoku::Cell::new(0, 7), sudoku::Cell::new(1, 7)],
+            difference: "3".to_owned(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '4', '5')));
+    }
+
+    #[test]
+    fn test_black_kropki_ratio() {
+        // R1C3 and R1C4 are a 3 and a 9.
+        let constraint = BlackKropkiConstraint {
+            cells: vec![sudoku::Cell::new(0, 2), sudoku::Cell::new(0, 3)],
+            ratio: "3".to_owned(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '8', '9')));
+    }
+
+    #[test]
+    fn test_negative_white_kropki_difference() {
+        let dots = adjacent_pairs()
+            .filter(|cells| (digit_at(GRID, cells[0]) - digit_at(GRID, cells[1])).abs() == 3)
+            .map(|cells| WhiteKropkiConstraint {
+                cells: cells.to_vec(),
+                difference: "3".to_owned(),
+            })
+            .collect::<Vec<_>>();
+        let negative = NegativeWhiteKropkiConstraint {
+            difference: "3".to_owned(),
+        };
+        // Places every dot of the grid, except for the first `skipped` ones.
+        let constraints = |skipped: usize| {
+            dots.iter()
+                .skip(skipped)
+                .map(|dot| dot as &dyn ConfigurableConstraint)
+                .chain([&negative as &dyn ConfigurableConstraint])
+                .collect::<Vec<_>>()
+        };
+        assert!(satisfies(&constraints(0), GRID));
+        assert!(!satisfies(&constraints(1), GRID));
+    }
+}
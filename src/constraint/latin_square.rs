@@ -1,11 +1,12 @@
 use z3::ast::Ast;
 use crate::constraint::Constraint;
-use crate::sudoku::SudokuContext;
+use crate::sudoku::{Cell, SudokuContext};
+use crate::z3_helper::Assertions;
 
 pub struct LatinSquareConstraint;
 
 impl Constraint for LatinSquareConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         for row in 0..context.height() {
             solver.assert(
                 context.bools().alloc(z3::ast::Int::distinct(
@@ -27,4 +28,21 @@ impl Constraint for LatinSquareConstraint {
             );
         }
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        for row in 0..context.height() {
+            solver.assert(context.one_hot_distinct(
+                &(0..context.width())
+                    .map(|col| Cell::new(row, col))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        for col in 0..context.width() {
+            solver.assert(context.one_hot_distinct(
+                &(0..context.height())
+                    .map(|row| Cell::new(row, col))
+                    .collect::<Vec<_>>(),
+            ));
+        }
+    }
 }
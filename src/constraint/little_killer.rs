@@ -5,6 +5,7 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 
 #[derive(DynClone)]
 #[dyn_clone(Constraint + Send)]
@@ -23,7 +24,7 @@ impl Default for LittleKillerConstraint {
 }
 
 impl Constraint for LittleKillerConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         let total: i32 = self.total.parse().unwrap();
 
         let cells = self
@@ -40,11 +41,13 @@ impl Constraint for LittleKillerConstraint {
 }
 
 impl ConfigurableConstraint for LittleKillerConstraint {
-    fn configure(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) {
+    fn configure(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
         ui.horizontal(|ui| {
             ui.label("Total");
-            ui.text_edit_singleline(&mut self.total);
+            changed |= ui.text_edit_singleline(&mut self.total).changed();
         });
+        changed
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
@@ -168,9 +171,14 @@ impl ConfigurableConstraint for LittleKillerConstraint {
             },
             -arrow_size,
         );
-        Self::draw_arrow(context.painter, arrow_tail, arrow_tip, context.color);
+        context.arrow(
+            arrow_tail,
+            arrow_tip,
+            2f32,
+            (arrow_tip - arrow_tail).length(),
+        );
 
-        context.painter.text(
+        context.text(
             arrow_tail,
             if positive_gradient {
                 egui::Align2::RIGHT_BOTTOM
@@ -178,27 +186,7 @@ impl ConfigurableConstraint for LittleKillerConstraint {
                 egui::Align2::LEFT_BOTTOM
             },
             &self.total,
-            egui::TextStyle::Body.resolve(context.style),
-            context.color,
+            context.body_text_size(),
         );
     }
 }
-
-impl LittleKillerConstraint {
-    fn draw_arrow(
-        painter: &egui::Painter,
-        tail: egui::Pos2,
-        tip: egui::Pos2,
-        color: egui::Color32,
-    ) {
-        let arrow_thickness = 2f32;
-
-        let stroke = egui::Stroke::new(arrow_thickness, color);
-        painter.line_segment([tail, tip], stroke);
-
-        let rot = egui::emath::Rot2::from_angle(std::f32::consts::PI * 3.0 / 4.0);
-        let vec = tip - tail;
-        painter.line_segment([tip, tip + rot * vec], stroke);
-        painter.line_segment([tip, tip + rot.inverse() * vec], stroke);
-    }
-}
@@ -0,0 +1,107 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct LockoutLineConstraint {
+    min_difference: String,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for LockoutLineConstraint {
+    fn default() -> Self {
+        Self {
+            min_difference: "4".to_owned(),
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for LockoutLineConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let (first, last) = (self.cells[0], self.cells[self.cells.len() - 1]);
+        let first = context.get_cell(first.row, first.col);
+        let last = context.get_cell(last.row, last.col);
+        let ascending = context.bools().alloc(first.lt(last));
+        let low = context.ints().alloc(ascending.ite(first, last));
+        let high = context.ints().alloc(ascending.ite(last, first));
+
+        solver.assert(
+            context.bools().alloc(
+                z3::ast::Int::sub(context.ctx(), &[high, low])
+                    .ge(context.const_int(self.min_difference.parse().unwrap())),
+            ),
+        );
+        for cell in &self.cells[1..self.cells.len() - 1] {
+            let cell = context.get_cell(cell.row, cell.col);
+            solver.assert(context.bools().alloc(z3::ast::Bool::or(
+                context.ctx(),
+                &[
+                    context.bools().alloc(cell.lt(low)),
+                    context.bools().alloc(cell.gt(high)),
+                ],
+            )));
+        }
+    }
+}
+
+impl ConfigurableConstraint for LockoutLineConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Minimum difference");
+            changed |= ui.text_edit_singleline(&mut self.min_difference).changed();
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 2 && self.min_difference.parse::<i32>().is_ok()
+    }
+
+    fn name(&self) -> &'static str {
+        "Lockout Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        if self.cells.len() < 2 {
+            context.default_draw();
+            return;
+        }
+
+        let line_width = 0.1;
+        let diamond_radius = 0.4;
+
+        let cell_width = context.cell_rect(0, 0).width();
+        let radius = cell_width * diamond_radius;
+        renban::draw_line_with_end_shapes(
+            &self.cells,
+            context,
+            |dir| radius / (dir.x.abs() + dir.y.abs()),
+            |center| {
+                let corners = [
+                    center + egui::Vec2::new(0.0, -radius),
+                    center + egui::Vec2::new(radius, 0.0),
+                    center + egui::Vec2::new(0.0, radius),
+                    center + egui::Vec2::new(-radius, 0.0),
+                ];
+                for i in 0..corners.len() {
+                    context.line(
+                        [corners[i], corners[(i + 1) % corners.len()]],
+                        cell_width * line_width * 0.5,
+                    );
+                }
+            },
+        );
+    }
+}
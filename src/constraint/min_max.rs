@@ -0,0 +1,138 @@
+use crate::constraint::inequality::draw_chevron;
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+
+#[derive(Clone, Eq, PartialEq)]
+enum Extremum {
+    Minimum,
+    Maximum,
+}
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct MinMaxConstraint {
+    extremum: Extremum,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for MinMaxConstraint {
+    fn default() -> Self {
+        Self {
+            extremum: Extremum::Maximum,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for MinMaxConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let cell = self.cells[0];
+        let value = context.get_cell(cell.row, cell.col);
+        for neighbour in context.orthogonal_neighbours(cell) {
+            let neighbour = context.get_cell(neighbour.row, neighbour.col);
+            let constraint = match self.extremum {
+                Extremum::Minimum => value.lt(neighbour),
+                Extremum::Maximum => value.gt(neighbour),
+            };
+            solver.assert(context.bools().alloc(constraint));
+        }
+    }
+}
+
+impl ConfigurableConstraint for MinMaxConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Type");
+            egui::ComboBox::from_id_source("select_extremum")
+                .selected_text(match self.extremum {
+                    Extremum::Minimum => "Minimum",
+                    Extremum::Maximum => "Maximum",
+                })
+                .show_ui(ui, |ui| {
+                    changed |= ui
+                        .selectable_value(&mut self.extremum, Extremum::Minimum, "Minimum")
+                        .changed();
+                    changed |= ui
+                        .selectable_value(&mut self.extremum, Extremum::Maximum, "Maximum")
+                        .changed();
+                });
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        1
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() == 1
+    }
+
+    fn name(&self) -> &'static str {
+        "Minimum/Maximum"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        if self.cells.is_empty() {
+            context.default_draw();
+            return;
+        }
+
+        // A chevron along each edge, pointing out of a maximum and into a minimum.
+        let rect = context.cell_rect(self.cells[0].row, self.cells[0].col);
+        let size = rect.width() * 0.1;
+        for direction in [
+            egui::vec2(0.0, -1.0),
+            egui::vec2(0.0, 1.0),
+            egui::vec2(-1.0, 0.0),
+            egui::vec2(1.0, 0.0),
+        ] {
+            let edge = rect.center() + direction * (rect.width() * 0.5 - size);
+            match self.extremum {
+                Extremum::Maximum => draw_chevron(context, edge, direction, size),
+                Extremum::Minimum => {
+                    draw_chevron(context, edge - direction * size, -direction, size)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_maximum() {
+        // R1C4 is a 9.
+        let constraint = MinMaxConstraint {
+            extremum: Extremum::Maximum,
+            cells: vec![sudoku::Cell::new(0, 3)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '1', '9')));
+    }
+
+    #[test]
+    fn test_minimum() {
+        // R1C6 is a 1.
+        let constraint = MinMaxConstraint {
+            extremum: Extremum::Minimum,
+            cells: vec![sudoku::Cell::new(0, 5)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '1', '9')));
+    }
+}
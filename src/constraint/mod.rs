@@ -1,50 +1,106 @@
+mod anti_king;
 mod anti_knight;
+mod anti_queen;
 mod arrow;
+mod between_line;
 mod diagonal;
 mod digit_definition;
-mod equal_sum;
+mod disjoint_groups;
 mod entropic_line;
-mod german_whisper;
+mod extra_region;
 mod given_digit;
+mod indexing;
+mod inequality;
 mod killer_cage;
 mod kropki;
 mod latin_square;
 mod little_killer;
+mod lockout_line;
+mod min_max;
+mod modular_line;
+mod nabner;
+mod non_consecutive;
+mod offsets;
 mod palindrome;
 mod parity;
+mod parity_line;
+mod quadruple;
+mod region_sum_line;
 mod renban;
+mod sandwich_sum;
+mod skyscraper;
 mod standard_boxes;
+#[cfg(test)]
+mod test_util;
 mod thermo;
+mod whisper;
 mod x_sum;
+mod xv;
+mod zipper;
 
 use std::any::Any;
-use crate::sudoku::SudokuContext;
+use crate::sudoku::{Encoding, SudokuContext};
 use crate::ui::SudokuDrawContext;
 use crate::{sudoku, DynClone};
+use crate::z3_helper::Assertions;
 use eframe::egui;
 
 pub use digit_definition::*;
 pub use given_digit::*;
+pub use killer_cage::KillerCageConstraint;
 pub use latin_square::*;
+pub use renban::RenbanConstraint;
 pub use standard_boxes::*;
 
+use anti_king::AntiKingConstraint;
 use anti_knight::AntiKnightConstraint;
+use anti_queen::AntiQueenConstraint;
 use arrow::ArrowConstraint;
+use between_line::BetweenLineConstraint;
 use diagonal::DiagonalConstraint;
-use equal_sum::EqualSumConstraint;
+use disjoint_groups::DisjointGroupsConstraint;
 use entropic_line::EntropicLineConstraint;
-use german_whisper::GermanWhisperConstraint;
-use killer_cage::KillerCageConstraint;
+use extra_region::ExtraRegionConstraint;
+use indexing::{IndexingConstraint, NumberedRoomConstraint};
+use inequality::{FortressConstraint, GreaterThanConstraint};
 use kropki::{BlackKropkiConstraint, NegativeBlackKropkiConstraint, NegativeWhiteKropkiConstraint, WhiteKropkiConstraint};
 use little_killer::LittleKillerConstraint;
+use lockout_line::LockoutLineConstraint;
+use min_max::MinMaxConstraint;
+use modular_line::ModularLineConstraint;
+use nabner::NabnerConstraint;
+use non_consecutive::NonConsecutiveConstraint;
 use palindrome::PalindromeConstraint;
 use parity::ParityConstraint;
-use renban::RenbanConstraint;
+use parity_line::ParityLineConstraint;
+use quadruple::QuadrupleConstraint;
+use region_sum_line::RegionSumLineConstraint;
+use sandwich_sum::SandwichSumConstraint;
+use skyscraper::SkyscraperConstraint;
 use thermo::ThermoConstraint;
+use whisper::WhisperConstraint;
 use x_sum::XSumConstraint;
+use xv::{NegativeXVConstraint, VConstraint, XConstraint};
+use zipper::ZipperConstraint;
 
 pub trait Constraint: Any {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext);
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext);
+    // Constraints without a boolean formulation fall back to the integer one, which still works
+    // because the cells are linked to their digit booleans.
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        self.apply(solver, context);
+    }
+    fn apply_encoded<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        match context.encoding() {
+            Encoding::Integer => self.apply(solver, context),
+            Encoding::OneHot => self.apply_one_hot(solver, context),
+        }
+    }
+    // Whether the assertions depend on the other constraints in the context, in which case they
+    // can't be reused once those change.
+    fn uses_other_constraints(&self) -> bool {
+        false
+    }
 }
 
 impl dyn Constraint + Send {
@@ -53,12 +109,25 @@ impl dyn Constraint + Send {
     }
 }
 
+// How right clicks on the grid select the highlighted cells of a constraint.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum CellSelection {
+    // Each click toggles the clicked cell.
+    Cells,
+    // A click selects the 2x2 block around the grid corner nearest to it.
+    Block,
+}
+
 pub trait ConfigurableConstraint: Constraint + DynClone<dyn Constraint + Send> {
-    fn configure(&mut self, ctx: &egui::Context, ui: &mut egui::Ui);
+    // Returns whether the constraint was changed.
+    fn configure(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) -> bool;
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>>;
     fn get_max_highlighted_cells(&self) -> usize {
         usize::MAX
     }
+    fn cell_selection(&self) -> CellSelection {
+        CellSelection::Cells
+    }
     fn is_valid(&self) -> bool;
     fn name(&self) -> &'static str;
     fn draw(&self, context: &SudokuDrawContext) {
@@ -70,26 +139,56 @@ pub trait ConfigurableConstraint: Constraint + DynClone<dyn Constraint + Send> {
     fn draw_depth(&self) -> i32 {
         0
     }
+    // A colour that identifies the variant, used instead of the colour assigned in the list.
+    fn color(&self) -> Option<egui::Color32> {
+        None
+    }
 }
 
 pub static CONFIGURABLES: phf::Map<&'static str, fn() -> Box<dyn ConfigurableConstraint + Send>> = phf::phf_map! {
+    "Anti-King" => || Box::<AntiKingConstraint>::default(),
     "Anti-Knight" => || Box::<AntiKnightConstraint>::default(),
+    "Anti-Queen" => || Box::<AntiQueenConstraint>::default(),
     "Arrow" => || Box::<ArrowConstraint>::default(),
+    "Between Line" => || Box::<BetweenLineConstraint>::default(),
     "Black Kropki Dot" => || Box::<BlackKropkiConstraint>::default(),
     "Black Kropki Dots (Negative Constraint)" => || Box::<NegativeBlackKropkiConstraint>::default(),
     "Diagonal" => || Box::<DiagonalConstraint>::default(),
-    "Equal Sum" => || Box::<EqualSumConstraint>::default(),
+    "Disjoint Groups" => || Box::<DisjointGroupsConstraint>::default(),
     "Entropic Line" => || Box::<EntropicLineConstraint>::default(),
-    "German Whisper" => || Box::<GermanWhisperConstraint>::default(),
+    // Former name of "Region Sum Line", kept so setups using it still load.
+    "Equal Sum" => || Box::<RegionSumLineConstraint>::default(),
+    "Extra Region" => || Box::<ExtraRegionConstraint>::default(),
+    "Fortress" => || Box::<FortressConstraint>::default(),
+    // Former name of "Whisper", kept so setups using it still load.
+    "German Whisper" => || Box::<WhisperConstraint>::default(),
+    "Greater Than" => || Box::<GreaterThanConstraint>::default(),
+    "Indexing Cells" => || Box::<IndexingConstraint>::default(),
     "Killer Cage" => || Box::<KillerCageConstraint>::default(),
     "Little Killer" => || Box::<LittleKillerConstraint>::default(),
+    "Lockout Line" => || Box::<LockoutLineConstraint>::default(),
+    "Minimum/Maximum" => || Box::<MinMaxConstraint>::default(),
+    "Modular Line" => || Box::<ModularLineConstraint>::default(),
+    "Nabner Line" => || Box::<NabnerConstraint>::default(),
+    "Non-Consecutive" => || Box::<NonConsecutiveConstraint>::default(),
+    "Numbered Room" => || Box::<NumberedRoomConstraint>::default(),
     "Palindrome" => || Box::<PalindromeConstraint>::default(),
     "Parity" => || Box::<ParityConstraint>::default(),
+    "Parity Line" => || Box::<ParityLineConstraint>::default(),
+    "Quadruple" => || Box::<QuadrupleConstraint>::default(),
+    "Region Sum Line" => || Box::<RegionSumLineConstraint>::default(),
     "Renban" => || Box::<RenbanConstraint>::default(),
+    "Sandwich Sum" => || Box::<SandwichSumConstraint>::default(),
+    "Skyscraper" => || Box::<SkyscraperConstraint>::default(),
     "Thermo" => || Box::<ThermoConstraint>::default(),
+    "V" => || Box::<VConstraint>::default(),
+    "Whisper" => || Box::<WhisperConstraint>::default(),
     "White Kropki Dot" => || Box::<WhiteKropkiConstraint>::default(),
     "White Kropki Dots (Negative Constraint)" => || Box::<NegativeWhiteKropkiConstraint>::default(),
+    "X" => || Box::<XConstraint>::default(),
     "X-Sum" => || Box::<XSumConstraint>::default(),
+    "XV (Negative Constraint)" => || Box::<NegativeXVConstraint>::default(),
+    "Zipper Line" => || Box::<ZipperConstraint>::default(),
 };
 
 pub fn make_default_constraint() -> Box<dyn ConfigurableConstraint> {
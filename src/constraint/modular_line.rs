@@ -0,0 +1,74 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct ModularLineConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for ModularLineConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let modulus = context.const_int(3);
+
+        for window in self.cells.windows(3) {
+            solver.assert(
+                context.bools().alloc(z3::ast::Int::distinct(
+                    context.ctx(),
+                    &window
+                        .iter()
+                        .map(|cell| {
+                            context
+                                .ints()
+                                .alloc(context.get_cell(cell.row, cell.col).modulo(modulus))
+                        })
+                        .collect::<Vec<_>>(),
+                )),
+            )
+        }
+    }
+}
+
+impl ConfigurableConstraint for ModularLineConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 3
+    }
+
+    fn name(&self) -> &'static str {
+        "Modular Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        renban::draw_line_between_cells(&self.cells, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_modular_line() {
+        // R1C1 to R1C3 read 483, one digit from each residue class.
+        let constraint = ModularLineConstraint {
+            cells: (0..3).map(|col| sudoku::Cell::new(0, col)).collect(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '3', '7')));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct NabnerConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for NabnerConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // All digits on the line are distinct and no two of them are consecutive, so every pair
+        // of digits differs by at least 2.
+        for (i, a) in self.cells.iter().enumerate() {
+            for b in &self.cells[i + 1..] {
+                let delta = z3::ast::Int::sub(
+                    context.ctx(),
+                    &[
+                        context.get_cell(a.row, a.col),
+                        context.get_cell(b.row, b.col),
+                    ],
+                );
+                let minus_delta = delta.unary_minus();
+                solver.assert(context.bools().alloc(z3::ast::Bool::or(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(delta.ge(context.const_int(2))),
+                        context.bools().alloc(minus_delta.ge(context.const_int(2))),
+                    ],
+                )));
+            }
+        }
+    }
+}
+
+impl ConfigurableConstraint for NabnerConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 2
+    }
+
+    fn name(&self) -> &'static str {
+        "Nabner Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        renban::draw_line_between_cells(&self.cells, context);
+    }
+}
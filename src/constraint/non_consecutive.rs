@@ -0,0 +1,51 @@
+use eframe::egui::{Context, Ui};
+use crate::constraint::kropki::{negative_constraint, white_kropki_constraint};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku::{Cell, SudokuContext};
+use ahash::AHashSet;
+use macros::DynClone;
+use crate::z3_helper::Assertions;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct NonConsecutiveConstraint;
+
+impl Constraint for NonConsecutiveConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // Like the negative white kropki constraint, but no dot exempts a pair.
+        negative_constraint(solver, context, &AHashSet::new(), |a, b, context| {
+            white_kropki_constraint(a, b, 1, context)
+        });
+    }
+}
+
+impl ConfigurableConstraint for NonConsecutiveConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<Cell>> {
+        None
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Non-Consecutive"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, GRID, MIRACLE_GRID};
+
+    #[test]
+    fn test_non_consecutive() {
+        assert!(satisfies(&[&NonConsecutiveConstraint], MIRACLE_GRID));
+        // R1C5 and R1C6 are a 2 and a 1.
+        assert!(!satisfies(&[&NonConsecutiveConstraint], GRID));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::sudoku::SudokuContext;
+use crate::z3_helper::Assertions;
+use z3::ast::Ast;
+
+// Calls `f` with every pair of cells that are separated by one of the offsets. Each offset only
+// needs to be given in one direction, since the opposite one yields the same pairs.
+fn for_each_pair_at_offsets(
+    context: &SudokuContext,
+    offsets: &[(isize, isize)],
+    mut f: impl FnMut((usize, usize), (usize, usize)),
+) {
+    for row in 0..context.height() {
+        for col in 0..context.width() {
+            for &(dr, dc) in offsets {
+                let other_row = row as isize + dr;
+                let other_col = col as isize + dc;
+                if other_row < 0
+                    || other_col < 0
+                    || other_row as usize >= context.height()
+                    || other_col as usize >= context.width()
+                {
+                    continue;
+                }
+                f((row, col), (other_row as usize, other_col as usize));
+            }
+        }
+    }
+}
+
+// Asserts that cells separated by one of the offsets don't contain the same digit, or, with
+// `digit`, that they don't both contain that digit.
+pub fn distinct_at_offsets(
+    solver: &Assertions,
+    context: &SudokuContext,
+    offsets: &[(isize, isize)],
+    digit: Option<usize>,
+) {
+    for_each_pair_at_offsets(context, offsets, |(row, col), (other_row, other_col)| {
+        let a = context.get_cell(row, col);
+        let b = context.get_cell(other_row, other_col);
+        let clause = match digit {
+            Some(digit) => {
+                let digit = context.const_int(digit as i32);
+                z3::ast::Bool::and(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(a._eq(digit)),
+                        context.bools().alloc(b._eq(digit)),
+                    ],
+                )
+                .not()
+            }
+            None => a._eq(b).not(),
+        };
+        solver.assert(context.bools().alloc(clause));
+    });
+}
+
+pub fn distinct_at_offsets_one_hot(
+    solver: &Assertions,
+    context: &SudokuContext,
+    offsets: &[(isize, isize)],
+    digit: Option<usize>,
+) {
+    for_each_pair_at_offsets(context, offsets, |(row, col), (other_row, other_col)| {
+        let digits = match digit {
+            Some(digit) => digit..=digit,
+            None => context.digits_range(),
+        };
+        for digit in digits {
+            solver.assert(context.at_most_one(&[
+                context.cell_is(row, col, digit),
+                context.cell_is(other_row, other_col, digit),
+            ]));
+        }
+    });
+}
@@ -4,7 +4,7 @@ use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
+use crate::z3_helper::Assertions;
 use crate::ui::SudokuDrawContext;
 
 #[derive(Default, DynClone)]
@@ -14,7 +14,7 @@ pub struct PalindromeConstraint {
 }
 
 impl Constraint for PalindromeConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         for i in 0..self.cells.len() / 2 {
             solver.assert(
                 context
@@ -31,7 +31,8 @@ impl Constraint for PalindromeConstraint {
 }
 
 impl ConfigurableConstraint for PalindromeConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
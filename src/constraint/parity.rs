@@ -5,7 +5,7 @@ use eframe::egui;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
+use crate::z3_helper::Assertions;
 use crate::ui::SudokuDrawContext;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -31,7 +31,7 @@ impl Default for ParityConstraint {
 }
 
 impl Constraint for ParityConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         let is_even = context
             .get_cell(self.cells[0].row, self.cells[0].col)
             .modulo(context.const_int(2))
@@ -44,7 +44,8 @@ impl Constraint for ParityConstraint {
 }
 
 impl ConfigurableConstraint for ParityConstraint {
-    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
         ui.horizontal(|ui| {
             ui.label("Parity");
             egui::ComboBox::from_id_source("select_parity")
@@ -53,10 +54,13 @@ impl ConfigurableConstraint for ParityConstraint {
                     Parity::Even => "Even",
                 })
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.parity, Parity::Odd, "Odd");
-                    ui.selectable_value(&mut self.parity, Parity::Even, "Even");
+                    changed |= ui.selectable_value(&mut self.parity, Parity::Odd, "Odd").changed();
+                    changed |= ui
+                        .selectable_value(&mut self.parity, Parity::Even, "Even")
+                        .changed();
                 });
         });
+        changed
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
@@ -85,8 +89,8 @@ impl ConfigurableConstraint for ParityConstraint {
             let ratio = 0.8;
             let rect = context.cell_rect(cell.row, cell.col);
             match self.parity {
-                Parity::Odd => context.painter.circle_filled(rect.center(), rect.width() * ratio * 0.5, context.color),
-                Parity::Even => context.painter.rect_filled(rect.shrink(rect.width() * (1.0 - ratio) * 0.5), 0f32, context.color),
+                Parity::Odd => context.circle_filled(rect.center(), rect.width() * ratio * 0.5),
+                Parity::Even => context.rect_filled(rect.shrink(rect.width() * (1.0 - ratio) * 0.5)),
             }
         }
     }
@@ -0,0 +1,72 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct ParityLineConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for ParityLineConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // Adjacent digits alternate between odd and even, so each adjacent pair has an odd sum.
+        for [prev, next] in self.cells.array_windows::<2>() {
+            let sum = z3::ast::Int::add(
+                context.ctx(),
+                &[
+                    context.get_cell(prev.row, prev.col),
+                    context.get_cell(next.row, next.col),
+                ],
+            );
+            solver.assert(
+                context
+                    .bools()
+                    .alloc(sum.modulo(context.const_int(2))._eq(context.const_int(1))),
+            );
+        }
+    }
+}
+
+impl ConfigurableConstraint for ParityLineConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 2
+    }
+
+    fn name(&self) -> &'static str {
+        "Parity Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        renban::draw_line_between_cells(&self.cells, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_parity_line() {
+        // R2C1 to R2C3 read 967.
+        let constraint = ParityLineConstraint {
+            cells: (0..3).map(|col| sudoku::Cell::new(1, col)).collect(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '5', '6')));
+    }
+}
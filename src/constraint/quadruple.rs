@@ -0,0 +1,157 @@
+use crate::constraint::{CellSelection, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::{SudokuContext, SUDOKU_SIZE};
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct QuadrupleConstraint {
+    cells: Vec<sudoku::Cell>,
+    digits: String,
+}
+
+impl QuadrupleConstraint {
+    // The block is selected top left cell first.
+    fn top_left(&self) -> Option<sudoku::Cell> {
+        self.cells.first().copied()
+    }
+
+    fn digits(&self) -> impl Iterator<Item = i32> + '_ {
+        self.digits
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|digit| digit as i32)
+    }
+}
+
+impl Constraint for QuadrupleConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let cells = self
+            .cells
+            .iter()
+            .map(|cell| context.get_cell(cell.row, cell.col))
+            .collect::<Vec<_>>();
+
+        // A digit listed several times must appear at least that many times.
+        let mut counts = [0; SUDOKU_SIZE + 1];
+        for digit in self.digits() {
+            counts[digit as usize] += 1;
+        }
+        for (digit, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let digit = context.const_int(digit as i32);
+            let occurrences = cells
+                .iter()
+                .map(|cell| {
+                    context.ints().alloc(
+                        cell._eq(digit)
+                            .ite(context.const_int(1), context.const_int(0)),
+                    )
+                })
+                .collect::<Vec<_>>();
+            solver.assert(context.bools().alloc(
+                z3::ast::Int::add(context.ctx(), &occurrences).ge(context.const_int(count)),
+            ));
+        }
+    }
+}
+
+impl ConfigurableConstraint for QuadrupleConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Digits");
+            changed |= ui.text_edit_singleline(&mut self.digits).changed();
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        4
+    }
+
+    fn cell_selection(&self) -> CellSelection {
+        CellSelection::Block
+    }
+
+    fn is_valid(&self) -> bool {
+        let digits_valid = self.digits.chars().all(
+            |c| matches!(c.to_digit(10), Some(digit) if (1..=SUDOKU_SIZE as u32).contains(&digit)),
+        );
+        self.cells.len() == 4 && digits_valid && (1..=4).contains(&self.digits.chars().count())
+    }
+
+    fn name(&self) -> &'static str {
+        "Quadruple"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        let top_left = match self.top_left() {
+            Some(top_left) => top_left,
+            None => {
+                context.default_draw();
+                return;
+            }
+        };
+
+        let center = context.cell_rect(top_left.row, top_left.col).right_bottom();
+        let radius = context.cell_rect(0, 0).width() * 0.25;
+        context.circle_stroke(center, radius, 1.0);
+
+        // The digits are laid out in rows of two, like pencil marks.
+        let digits = self.digits.chars().collect::<Vec<_>>();
+        let offset = radius * 0.4;
+        for (i, digit) in digits.iter().enumerate() {
+            let dx = if digits.len() == 1 || (i == 2 && digits.len() == 3) {
+                0.0
+            } else if i % 2 == 0 {
+                -offset
+            } else {
+                offset
+            };
+            let dy = if digits.len() <= 2 {
+                0.0
+            } else if i < 2 {
+                -offset
+            } else {
+                offset
+            };
+            context.text(
+                center + egui::vec2(dx, dy),
+                egui::Align2::CENTER_CENTER,
+                &digit.to_string(),
+                radius * 0.8,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_quadruple() {
+        // The top left corner holds 4, 8, 9 and 6.
+        let constraint = QuadrupleConstraint {
+            cells: vec![
+                sudoku::Cell::new(0, 0),
+                sudoku::Cell::new(0, 1),
+                sudoku::Cell::new(1, 0),
+                sudoku::Cell::new(1, 1),
+            ],
+            digits: "48".to_owned(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '4', '5')));
+    }
+}
@@ -4,31 +4,33 @@ use crate::constraint::{ConfigurableConstraint, Constraint, renban};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use macros::DynClone;
-use z3::Solver;
+use crate::z3_helper::Assertions;
 use crate::ui::SudokuDrawContext;
 
 #[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
-pub struct EqualSumConstraint {
+pub struct RegionSumLineConstraint {
     cells: Vec<sudoku::Cell>,
 }
 
-impl Constraint for EqualSumConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
-        let mut cells_in_boxes = Vec::new();
-        let mut current_box: Option<sudoku::Cell> = None;
+impl Constraint for RegionSumLineConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // The line is split into segments wherever it crosses into another region. A line that
+        // leaves a region and comes back later has separate segments for each visit.
+        let mut segments = Vec::new();
+        let mut current_region = None;
         for &cell in &self.cells {
-            let cell_box = sudoku::Cell::new(cell.row / 3, cell.col / 3);
-            if !current_box.contains(&cell_box) {
-                current_box = Some(cell_box);
-                cells_in_boxes.push(Vec::new());
+            let region = context.region(cell);
+            if !current_region.contains(&region) {
+                current_region = Some(region);
+                segments.push(Vec::new());
             }
-            cells_in_boxes.last_mut().unwrap().push(cell);
+            segments.last_mut().unwrap().push(cell);
         }
-        if cells_in_boxes.len() < 2 {
+        if segments.len() < 2 {
             return;
         }
-        let sums: Vec<_> = cells_in_boxes
+        let sums: Vec<_> = segments
             .iter()
             .map(|cells| {
                 context.ints().alloc(z3::ast::Int::add(
@@ -52,8 +54,9 @@ impl Constraint for EqualSumConstraint {
     }
 }
 
-impl ConfigurableConstraint for EqualSumConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {
+impl ConfigurableConstraint for RegionSumLineConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
@@ -65,7 +68,7 @@ impl ConfigurableConstraint for EqualSumConstraint {
     }
 
     fn name(&self) -> &'static str {
-        "Equal Sum"
+        "Region Sum Line"
     }
 
     fn draw(&self, context: &SudokuDrawContext) {
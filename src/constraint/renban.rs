@@ -2,11 +2,11 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 use eframe::egui;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use z3::ast::Ast;
-use z3::Solver;
 
 pub fn draw_line_between_cells(cells: &[sudoku::Cell], context: &SudokuDrawContext) {
     for [prev, next] in cells.array_windows::<2>() {
@@ -21,30 +21,71 @@ pub fn draw_line_between_cells(cells: &[sudoku::Cell], context: &SudokuDrawConte
     for [prev, next] in cells.array_windows::<2>() {
         let prev_rect = context.cell_rect(prev.row, prev.col);
         let next_rect = context.cell_rect(next.row, next.col);
-        context.painter.line_segment(
+        context.line(
             [prev_rect.center(), next_rect.center()],
-            egui::Stroke::new(prev_rect.width() * renban_width, context.color),
+            prev_rect.width() * renban_width,
         );
     }
 
     for cell in cells {
         let rect = context.cell_rect(cell.row, cell.col);
-        context
-            .painter
-            .circle_filled(rect.center(), rect.width() * renban_width * 0.5, context.color);
+        context.circle_filled(rect.center(), rect.width() * renban_width * 0.5);
     }
 }
 
+// Draws a line through the cells with a hollow shape on both ends. The line is joined to the edges
+// of the shapes rather than their centres, so the shapes stay empty. `edge_distance` gives the
+// distance from the centre of a shape to its edge in a direction, and `draw_shape` draws a shape
+// around a centre.
+pub fn draw_line_with_end_shapes(
+    cells: &[sudoku::Cell],
+    context: &SudokuDrawContext,
+    edge_distance: impl Fn(egui::Vec2) -> f32,
+    draw_shape: impl Fn(egui::Pos2),
+) {
+    let line_width = 0.1;
+
+    draw_line_between_cells(&cells[1..cells.len() - 1], context);
+
+    let first = context.cell_rect(cells[0].row, cells[0].col);
+    let last = context.cell_rect(cells[cells.len() - 1].row, cells[cells.len() - 1].col);
+    let width = first.width() * line_width;
+    if cells.len() == 2 {
+        // Both ends share the same connector, so only draw it once.
+        let dir = (last.center() - first.center()).normalized();
+        context.line(
+            [
+                first.center() + dir * edge_distance(dir),
+                last.center() - dir * edge_distance(-dir),
+            ],
+            width,
+        );
+    } else {
+        let ends = [(cells[0], cells[1]), (cells[cells.len() - 1], cells[cells.len() - 2])];
+        for (end, next) in ends {
+            let end = context.cell_rect(end.row, end.col).center();
+            let next = context.cell_rect(next.row, next.col).center();
+            let dir = (next - end).normalized();
+            context.line([end + dir * edge_distance(dir), next], width);
+        }
+    }
+
+    draw_shape(first.center());
+    draw_shape(last.center());
+}
+
 #[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
 pub struct RenbanConstraint {
     cells: Vec<sudoku::Cell>,
 }
 
-impl Constraint for RenbanConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
-        // The renban constraint can be subdivided into two separate constraints:
-        // 1. All digits on the renban are distinct...
+impl RenbanConstraint {
+    pub fn new(cells: Vec<sudoku::Cell>) -> Self {
+        Self { cells }
+    }
+
+    fn assert_distinct(&self, solver: &Assertions, context: &SudokuContext) {
         solver.assert(
             context.bools().alloc(z3::ast::Int::distinct(
                 context.ctx(),
@@ -55,7 +96,13 @@ impl Constraint for RenbanConstraint {
                     .collect::<Vec<_>>(),
             )),
         );
-        // 2. All digits on the renban are consecutive. If all digits are distinct, then
+    }
+
+    // The original encoding of the consecutiveness, which needs quantifier reasoning. This is slow
+    // with several renbans, and is only kept to compare against in the renban benchmark.
+    pub fn apply_quantified(&self, solver: &Assertions, context: &SudokuContext) {
+        self.assert_distinct(solver, context);
+        // All digits on the renban are consecutive. If all digits are distinct, then
         // consecutiveness implies that the minimum digit is n-1 different from the maximum digit,
         // or suffice to say that no pair of digits on the renban differ by n or more.
         // More formally, we say that there does *not* exist a pair of integers (x, y) such that
@@ -106,8 +153,64 @@ impl Constraint for RenbanConstraint {
     }
 }
 
+impl Constraint for RenbanConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // The renban constraint can be subdivided into two separate constraints:
+        // 1. All digits on the renban are distinct...
+        self.assert_distinct(solver, context);
+        // 2. All digits on the renban are consecutive. If all digits are distinct, then
+        // consecutiveness is equivalent to no pair of digits on the renban differing by n or more.
+        let n = context.const_int(self.cells.len() as i32);
+        for (i, a) in self.cells.iter().enumerate() {
+            for b in &self.cells[i + 1..] {
+                let a = context.get_cell(a.row, a.col);
+                let b = context.get_cell(b.row, b.col);
+                solver.assert(context.bools().alloc(
+                    z3::ast::Int::sub(context.ctx(), &[a, b]).lt(n),
+                ));
+                solver.assert(context.bools().alloc(
+                    z3::ast::Int::sub(context.ctx(), &[b, a]).lt(n),
+                ));
+            }
+        }
+    }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        solver.assert(context.one_hot_distinct(&self.cells));
+        // The digits on the renban all lie in one window of n consecutive digits, so every digit
+        // outside the window is ruled out for every cell.
+        let n = self.cells.len();
+        let digits = context.digits_range();
+        let windows = (*digits.start()..=(digits.end() + 1).saturating_sub(n))
+            .map(|low| {
+                context.bools().alloc(z3::ast::Bool::and(
+                    context.ctx(),
+                    &digits
+                        .clone()
+                        .filter(|digit| !(low..low + n).contains(digit))
+                        .flat_map(|digit| {
+                            self.cells.iter().map(move |cell| {
+                                context
+                                    .bools()
+                                    .alloc(context.cell_is(cell.row, cell.col, digit).not())
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        solver.assert(
+            context
+                .bools()
+                .alloc(z3::ast::Bool::or(context.ctx(), &windows)),
+        );
+    }
+}
+
 impl ConfigurableConstraint for RenbanConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -0,0 +1,136 @@
+use crate::constraint::x_sum::{
+    direction_combo, draw_number_outside_grid, iter_cells_in_dir, Direction,
+};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct SandwichSumConstraint {
+    total: String,
+    direction: Direction,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for SandwichSumConstraint {
+    fn default() -> Self {
+        Self {
+            total: "0".to_owned(),
+            direction: Direction::Horizontal,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for SandwichSumConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let mut cells = Vec::new();
+        let valid = iter_cells_in_dir(self.cells[0], self.direction, context, |_, cell| {
+            cells.push(context.get_cell(cell.row, cell.col));
+        });
+        if !valid {
+            return;
+        }
+
+        let min_digit = context.const_int(*context.digits_range().start() as i32);
+        let max_digit = context.const_int(*context.digits_range().end() as i32);
+        let is_crust = cells
+            .iter()
+            .map(|cell| {
+                context.bools().alloc(z3::ast::Bool::or(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(cell._eq(min_digit)),
+                        context.bools().alloc(cell._eq(max_digit)),
+                    ],
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        // A cell is in the sandwich if there is a 1 or a 9 on both sides of it. There is only one
+        // of each in the row or column, so this never holds for the crusts themselves.
+        let sandwiched = (0..cells.len())
+            .map(|i| {
+                let before = context
+                    .bools()
+                    .alloc(z3::ast::Bool::or(context.ctx(), &is_crust[..i]));
+                let after = context
+                    .bools()
+                    .alloc(z3::ast::Bool::or(context.ctx(), &is_crust[i + 1..]));
+                context.ints().alloc(
+                    z3::ast::Bool::and(context.ctx(), &[before, after])
+                        .ite(cells[i], context.const_int(0)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        solver.assert(
+            context.bools().alloc(
+                z3::ast::Int::add(context.ctx(), &sandwiched)
+                    ._eq(context.const_int(self.total.parse().unwrap())),
+            ),
+        );
+    }
+}
+
+impl ConfigurableConstraint for SandwichSumConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.label(
+            "Place the sandwich sum on the perimeter of the grid with the appropriate direction.",
+        );
+        ui.horizontal(|ui| {
+            ui.label("Total");
+            changed |= ui.text_edit_singleline(&mut self.total).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Direction");
+            changed |= direction_combo("choose_direction", &mut self.direction, ui);
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        1
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.cells.is_empty() && self.total.parse::<i32>().is_ok()
+    }
+
+    fn name(&self) -> &'static str {
+        "Sandwich Sum"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_number_outside_grid(&self.cells, &self.total, self.direction, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_sandwich_sum() {
+        // The first row is 483921657, with a 2 between the 9 and the 1.
+        let constraint = SandwichSumConstraint {
+            total: "2".to_owned(),
+            direction: Direction::Horizontal,
+            cells: vec![sudoku::Cell::new(0, 0)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '2', '3')));
+    }
+}
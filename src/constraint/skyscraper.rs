@@ -0,0 +1,118 @@
+use crate::constraint::x_sum::{
+    direction_combo, draw_number_outside_grid, iter_cells_in_dir, Direction,
+};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct SkyscraperConstraint {
+    visible: String,
+    direction: Direction,
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Default for SkyscraperConstraint {
+    fn default() -> Self {
+        Self {
+            visible: "1".to_owned(),
+            direction: Direction::Horizontal,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl Constraint for SkyscraperConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let mut cells = Vec::new();
+        let valid = iter_cells_in_dir(self.cells[0], self.direction, context, |_, cell| {
+            cells.push(context.get_cell(cell.row, cell.col));
+        });
+        if !valid {
+            return;
+        }
+
+        // A skyscraper is visible if it is taller than every skyscraper in front of it.
+        let visible = (0..cells.len())
+            .map(|i| {
+                let taller = cells[..i]
+                    .iter()
+                    .map(|before| context.bools().alloc(cells[i].gt(before)))
+                    .collect::<Vec<_>>();
+                context.ints().alloc(
+                    z3::ast::Bool::and(context.ctx(), &taller)
+                        .ite(context.const_int(1), context.const_int(0)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        solver.assert(
+            context.bools().alloc(
+                z3::ast::Int::add(context.ctx(), &visible)
+                    ._eq(context.const_int(self.visible.parse().unwrap())),
+            ),
+        );
+    }
+}
+
+impl ConfigurableConstraint for SkyscraperConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.label(
+            "Place the skyscraper clue on the perimeter of the grid with the appropriate direction.",
+        );
+        ui.horizontal(|ui| {
+            ui.label("Visible");
+            changed |= ui.text_edit_singleline(&mut self.visible).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Direction");
+            changed |= direction_combo("choose_direction", &mut self.direction, ui);
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        1
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.cells.is_empty() && self.visible.parse::<i32>().is_ok()
+    }
+
+    fn name(&self) -> &'static str {
+        "Skyscraper"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_number_outside_grid(&self.cells, &self.visible, self.direction, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_skyscraper() {
+        // The first row is 483921657, where the 4, 8 and 9 are visible from the left.
+        let constraint = SkyscraperConstraint {
+            visible: "3".to_owned(),
+            direction: Direction::Horizontal,
+            cells: vec![sudoku::Cell::new(0, 0)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '4', '9')));
+    }
+}
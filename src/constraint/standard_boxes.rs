@@ -1,11 +1,12 @@
 use z3::ast::Ast;
 use crate::constraint::Constraint;
-use crate::sudoku::{SUDOKU_SIZE, SudokuContext};
+use crate::sudoku::{Cell, SUDOKU_SIZE, SudokuContext};
+use crate::z3_helper::Assertions;
 
 pub struct StandardBoxesConstraint;
 
 impl Constraint for StandardBoxesConstraint {
-    fn apply<'a>(&self, solver: &z3::Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         assert!(context.width() == SUDOKU_SIZE && context.height() == SUDOKU_SIZE);
         for x in 0..3 {
             for y in 0..3 {
@@ -22,4 +23,17 @@ impl Constraint for StandardBoxesConstraint {
             }
         }
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        assert!(context.width() == SUDOKU_SIZE && context.height() == SUDOKU_SIZE);
+        for x in 0..3 {
+            for y in 0..3 {
+                solver.assert(context.one_hot_distinct(
+                    &(0..3)
+                        .flat_map(|dx| (0..3).map(move |dy| Cell::new(x * 3 + dx, y * 3 + dy)))
+                        .collect::<Vec<_>>(),
+                ));
+            }
+        }
+    }
 }
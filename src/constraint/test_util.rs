@@ -0,0 +1,79 @@
+use crate::constraint::{ConfigurableConstraint, Constraint, GivenDigitConstraint};
+use crate::solver::{base_constraints, count_assertions};
+use crate::sudoku::{Cell, Encoding, SudokuContext, SUDOKU_SIZE};
+
+// A completed grid, read left to right and top to bottom.
+pub const GRID: &str =
+    "483921657967345821251876493548132976729564138136798245372689514814253769695417382";
+
+// A completed grid where cells a king's or knight's move apart never repeat a digit, orthogonal
+// neighbours are never consecutive, and the cells at each position within the boxes all differ.
+pub const MIRACLE_GRID: &str =
+    "483726159726159483159483726837261594261594837594837261372615948615948372948372615";
+
+// Relabels a grid by exchanging two digits, which keeps it a valid sudoku.
+pub fn swap_digits(grid: &str, a: char, b: char) -> String {
+    grid.chars()
+        .map(|c| match c {
+            _ if c == a => b,
+            _ if c == b => a,
+            _ => c,
+        })
+        .collect()
+}
+
+// The digit of a cell in a completed grid.
+pub fn digit_at(grid: &str, cell: Cell) -> i32 {
+    grid.as_bytes()[cell.row * SUDOKU_SIZE + cell.col] as i32 - '0' as i32
+}
+
+// Every pair of orthogonally adjacent cells, in reading order.
+pub fn adjacent_pairs() -> impl Iterator<Item = [Cell; 2]> {
+    (0..SUDOKU_SIZE).flat_map(|row| {
+        (0..SUDOKU_SIZE).flat_map(move |col| {
+            [Cell::new(row, col + 1), Cell::new(row + 1, col)]
+                .into_iter()
+                .filter(|other| other.row < SUDOKU_SIZE && other.col < SUDOKU_SIZE)
+                .map(move |other| [Cell::new(row, col), other])
+        })
+    })
+}
+
+// Whether a completed grid follows the standard rules and the given constraints. Both encodings
+// are checked, and have to agree.
+pub fn satisfies(constraints: &[&dyn ConfigurableConstraint], grid: &str) -> bool {
+    let results = Encoding::ALL.map(|encoding| {
+        let ctx = z3::Context::new(&z3::Config::new());
+        let solver = z3::Solver::new(&ctx);
+        let constraints = base_constraints()
+            .into_iter()
+            .map(|(_, constraint)| constraint)
+            .chain(constraints.iter().map(|constraint| constraint.dyn_clone()))
+            .collect::<Vec<Box<dyn Constraint + Send>>>();
+        let sudoku = SudokuContext::create(&ctx, &constraints, encoding);
+        for constraint in &constraints {
+            count_assertions(&solver, |solver| constraint.apply_encoded(solver, &sudoku));
+        }
+        count_assertions(&solver, |solver| {
+            for (i, c) in grid.chars().enumerate() {
+                let given = GivenDigitConstraint {
+                    row: i / SUDOKU_SIZE,
+                    col: i % SUDOKU_SIZE,
+                    value: c.to_digit(10).unwrap() as i32,
+                };
+                given.apply_encoded(solver, &sudoku);
+            }
+        });
+        solver.check() == z3::SatResult::Sat
+    });
+    assert_eq!(results[0], results[1], "Encodings disagree");
+    results[0]
+}
+
+#[test]
+fn test_grid_is_valid() {
+    assert!(satisfies(&[], GRID));
+    // Exchanging two cells of a row breaks their columns.
+    assert!(!satisfies(&[], &format!("{}{}", "843921657", &GRID[9..])));
+    assert!(satisfies(&[], MIRACLE_GRID));
+}
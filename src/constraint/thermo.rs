@@ -2,10 +2,9 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
-use eframe::egui;
+use crate::z3_helper::Assertions;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
-use z3::Solver;
 
 #[derive(Default, DynClone)]
 #[dyn_clone(Constraint + Send)]
@@ -14,7 +13,7 @@ pub struct ThermoConstraint {
 }
 
 impl Constraint for ThermoConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         for &[prev, next] in self.cells.array_windows::<2>() {
             solver.assert(
                 context.bools().alloc(
@@ -25,10 +24,47 @@ impl Constraint for ThermoConstraint {
             );
         }
     }
+
+    fn apply_one_hot<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let digits = context.digits_range();
+        // Each cell needs room for the increasing digits before and after it on the thermo.
+        for (i, cell) in self.cells.iter().enumerate() {
+            let after = self.cells.len() - 1 - i;
+            for digit in digits.clone() {
+                if digit < digits.start() + i || digit + after > *digits.end() {
+                    solver.assert(
+                        context
+                            .bools()
+                            .alloc(context.cell_is(cell.row, cell.col, digit).not()),
+                    );
+                }
+            }
+        }
+
+        // A digit in one cell means the next cell holds a larger digit.
+        for &[prev, next] in self.cells.array_windows::<2>() {
+            for digit in digits.clone() {
+                let larger = (digit + 1..=*digits.end())
+                    .map(|larger| context.cell_is(next.row, next.col, larger))
+                    .collect::<Vec<_>>();
+                solver.assert(
+                    context.bools().alloc(
+                        context.cell_is(prev.row, prev.col, digit).implies(
+                            context
+                                .bools()
+                                .alloc(z3::ast::Bool::or(context.ctx(), &larger)),
+                        ),
+                    ),
+                );
+            }
+        }
+    }
 }
 
 impl ConfigurableConstraint for ThermoConstraint {
-    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) {}
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
         Some(&mut self.cells)
@@ -59,28 +95,20 @@ impl ConfigurableConstraint for ThermoConstraint {
         let column_radius = 0.15;
 
         let first_rect = context.cell_rect(self.cells[0].row, self.cells[0].col);
-        context
-            .painter
-            .circle_filled(first_rect.center(), first_rect.width() * bulb_radius, context.color);
+        context.circle_filled(first_rect.center(), first_rect.width() * bulb_radius);
 
         for [prev, next] in self.cells.array_windows::<2>() {
             let prev_pos = context.cell_rect(prev.row, prev.col);
             let next_pos = context.cell_rect(next.row, next.col);
             let vec = next_pos.center() - prev_pos.center();
             let side = vec.rot90().normalized() * prev_pos.width() * column_radius;
-            context.painter.add(egui::epaint::PathShape::convex_polygon(
-                vec![
-                    next_pos.center() - side,
-                    next_pos.center() + side,
-                    prev_pos.center() + side,
-                    prev_pos.center() - side,
-                ],
-                context.color,
-                egui::Stroke::none(),
-            ));
-            context
-                .painter
-                .circle_filled(next_pos.center(), next_pos.width() * column_radius, context.color);
+            context.convex_polygon(vec![
+                next_pos.center() - side,
+                next_pos.center() + side,
+                prev_pos.center() + side,
+                prev_pos.center() - side,
+            ]);
+            context.circle_filled(next_pos.center(), next_pos.width() * column_radius);
         }
     }
 }
@@ -0,0 +1,129 @@
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use crate::constraint::{ConfigurableConstraint, Constraint, renban};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use macros::DynClone;
+use crate::z3_helper::Assertions;
+use crate::ui::SudokuDrawContext;
+
+const GERMAN_MIN_DIFFERENCE: &str = "5";
+const DUTCH_MIN_DIFFERENCE: &str = "4";
+
+#[derive(DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct WhisperConstraint {
+    cells: Vec<sudoku::Cell>,
+    min_difference: String,
+    // Empty if adjacent digits may differ by any amount above the minimum.
+    max_difference: String,
+}
+
+impl Default for WhisperConstraint {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            min_difference: GERMAN_MIN_DIFFERENCE.to_owned(),
+            max_difference: String::new(),
+        }
+    }
+}
+
+impl Constraint for WhisperConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        let min_difference = context.const_int(self.min_difference.parse().unwrap());
+        let max_difference = self
+            .max_difference
+            .parse()
+            .ok()
+            .map(|max_difference| context.const_int(max_difference));
+
+        for [prev, next] in self.cells.array_windows::<2>() {
+            let delta = z3::ast::Int::sub(
+                context.ctx(),
+                &[
+                    context.get_cell(next.row, next.col),
+                    context.get_cell(prev.row, prev.col),
+                ],
+            );
+            let minus_delta = delta.unary_minus();
+            solver.assert(context.bools().alloc(z3::ast::Bool::or(
+                context.ctx(),
+                &[
+                    context.bools().alloc(delta.ge(min_difference)),
+                    context.bools().alloc(minus_delta.ge(min_difference)),
+                ],
+            )));
+            if let Some(max_difference) = max_difference {
+                solver.assert(context.bools().alloc(delta.le(max_difference)));
+                solver.assert(context.bools().alloc(minus_delta.le(max_difference)));
+            }
+        }
+    }
+}
+
+impl ConfigurableConstraint for WhisperConstraint {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Preset");
+            if ui.button("German").clicked() {
+                changed = true;
+                self.min_difference = GERMAN_MIN_DIFFERENCE.to_owned();
+                self.max_difference.clear();
+            }
+            if ui.button("Dutch").clicked() {
+                changed = true;
+                self.min_difference = DUTCH_MIN_DIFFERENCE.to_owned();
+                self.max_difference.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Minimum difference");
+            changed |= ui.text_edit_singleline(&mut self.min_difference).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Maximum difference");
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut self.max_difference).hint_text("None"))
+                .changed();
+        });
+        changed
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        let min_difference = match self.min_difference.parse::<i32>() {
+            Ok(min_difference) => min_difference,
+            Err(_) => return false,
+        };
+        let max_difference_valid = self.max_difference.is_empty()
+            || matches!(
+                self.max_difference.parse::<i32>(),
+                Ok(max_difference) if max_difference >= min_difference
+            );
+        self.cells.len() >= 2 && max_difference_valid
+    }
+
+    fn name(&self) -> &'static str {
+        "Whisper"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        renban::draw_line_between_cells(&self.cells, context);
+    }
+
+    fn color(&self) -> Option<egui::Color32> {
+        if !self.max_difference.is_empty() {
+            return None;
+        }
+        match self.min_difference.as_str() {
+            GERMAN_MIN_DIFFERENCE => Some(egui::Color32::from_rgb(0x6a, 0xc2, 0x4c)),
+            DUTCH_MIN_DIFFERENCE => Some(egui::Color32::from_rgb(0xf0, 0x92, 0x3a)),
+            _ => None,
+        }
+    }
+}
@@ -2,12 +2,12 @@ use crate::constraint::{ConfigurableConstraint, Constraint};
 use crate::sudoku;
 use crate::sudoku::SudokuContext;
 use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
 use eframe::egui;
 use eframe::egui::{Context, Ui};
 use macros::DynClone;
 use std::ops::Add;
 use z3::ast::Ast;
-use z3::Solver;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Direction {
@@ -15,6 +15,22 @@ pub enum Direction {
     Vertical,
 }
 
+// Returns whether the direction was changed.
+pub(super) fn direction_combo(id: &str, direction: &mut Direction, ui: &mut Ui) -> bool {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(match direction {
+            Direction::Horizontal => "Horizontal",
+            Direction::Vertical => "Vertical",
+        })
+        .show_ui(ui, |ui| {
+            let horizontal = ui.selectable_value(direction, Direction::Horizontal, "Horizontal");
+            let vertical = ui.selectable_value(direction, Direction::Vertical, "Vertical");
+            horizontal.changed() || vertical.changed()
+        })
+        .inner
+        .unwrap_or(false)
+}
+
 pub fn iter_cells_in_dir(
     start_pos: sudoku::Cell,
     direction: Direction,
@@ -83,14 +99,13 @@ pub fn draw_number_outside_grid(
     };
 
     let cell_rect = context.cell_rect(pos.row, pos.col);
-    context.painter.text(
+    context.text(
         cell_rect
             .center()
             .add(egui::Vec2::new(dc as f32, dr as f32) * cell_rect.width() * 0.6),
         align,
         value,
-        context.get_font(0.5),
-        context.color,
+        context.text_size(0.5),
     );
 }
 
@@ -113,7 +128,7 @@ impl Default for XSumConstraint {
 }
 
 impl Constraint for XSumConstraint {
-    fn apply<'a>(&self, solver: &Solver, context: &'a SudokuContext) {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
         let start_pos = self.cells[0];
         let start_cell = context.get_cell(start_pos.row, start_pos.col);
         let mut sum = context.const_int(0);
@@ -140,24 +155,18 @@ impl Constraint for XSumConstraint {
 }
 
 impl ConfigurableConstraint for XSumConstraint {
-    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) {
+    fn configure(&mut self, _ctx: &Context, ui: &mut Ui) -> bool {
+        let mut changed = false;
         ui.label("Place the X-Sum on the perimeter of the grid with the appropriate direction.");
         ui.horizontal(|ui| {
             ui.label("Total");
-            ui.text_edit_singleline(&mut self.total);
+            changed |= ui.text_edit_singleline(&mut self.total).changed();
         });
         ui.horizontal(|ui| {
             ui.label("Direction");
-            egui::ComboBox::from_id_source("choose_direction")
-                .selected_text(match self.direction {
-                    Direction::Horizontal => "Horizontal",
-                    Direction::Vertical => "Vertical",
-                })
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.direction, Direction::Horizontal, "Horizontal");
-                    ui.selectable_value(&mut self.direction, Direction::Vertical, "Vertical");
-                });
+            changed |= direction_combo("choose_direction", &mut self.direction, ui);
         });
+        changed
     }
 
     fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
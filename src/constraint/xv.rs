@@ -0,0 +1,252 @@
+use crate::constraint::kropki::{draw_kropki_dot, negative_constraint, ordered_pair};
+use crate::constraint::{ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use ahash::AHashSet;
+use eframe::egui;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+const X_SUM: i32 = 10;
+const V_SUM: i32 = 5;
+
+fn sum_constraint<'a>(
+    a: sudoku::Cell,
+    b: sudoku::Cell,
+    sum: i32,
+    context: &'a SudokuContext<'a>,
+) -> z3::ast::Bool<'a> {
+    z3::ast::Int::add(
+        context.ctx(),
+        &[
+            context.get_cell(a.row, a.col),
+            context.get_cell(b.row, b.col),
+        ],
+    )
+    ._eq(context.const_int(sum))
+}
+
+fn draw_letter(cells: &[sudoku::Cell], context: &SudokuDrawContext, letter: &str) {
+    draw_kropki_dot(cells, context, |center, _radius| {
+        context.text(
+            center,
+            egui::Align2::CENTER_CENTER,
+            letter,
+            context.body_text_size(),
+        );
+    });
+}
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct XConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for XConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        solver.assert(context.bools().alloc(sum_constraint(
+            self.cells[0],
+            self.cells[1],
+            X_SUM,
+            context,
+        )));
+    }
+}
+
+impl ConfigurableConstraint for XConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        2
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() == 2
+    }
+
+    fn name(&self) -> &'static str {
+        "X"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_letter(&self.cells, context, "X");
+    }
+}
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct VConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for VConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        solver.assert(context.bools().alloc(sum_constraint(
+            self.cells[0],
+            self.cells[1],
+            V_SUM,
+            context,
+        )));
+    }
+}
+
+impl ConfigurableConstraint for VConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn get_max_highlighted_cells(&self) -> usize {
+        2
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() == 2
+    }
+
+    fn name(&self) -> &'static str {
+        "V"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        draw_letter(&self.cells, context, "V");
+    }
+}
+
+fn find_xv_markers(context: &SudokuContext) -> AHashSet<(sudoku::Cell, sudoku::Cell)> {
+    context
+        .constraints()
+        .iter()
+        .filter_map(|constraint| {
+            let cells = if let Some(x) = constraint.downcast::<XConstraint>() {
+                &x.cells
+            } else if let Some(v) = constraint.downcast::<VConstraint>() {
+                &v.cells
+            } else {
+                return None;
+            };
+            ordered_pair(cells)
+        })
+        .collect()
+}
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct NegativeXVConstraint;
+
+impl Constraint for NegativeXVConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        negative_constraint(
+            solver,
+            context,
+            &find_xv_markers(context),
+            |a, b, context| {
+                z3::ast::Bool::or(
+                    context.ctx(),
+                    &[
+                        context.bools().alloc(sum_constraint(a, b, X_SUM, context)),
+                        context.bools().alloc(sum_constraint(a, b, V_SUM, context)),
+                    ],
+                )
+            },
+        );
+    }
+
+    fn uses_other_constraints(&self) -> bool {
+        true
+    }
+}
+
+impl ConfigurableConstraint for NegativeXVConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        None
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "XV (Negative Constraint)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{adjacent_pairs, digit_at, satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_x() {
+        // R1C3 and R2C3 are a 3 and a 7.
+        let constraint = XConstraint {
+            cells: vec![sudoku::Cell::new(0, 2), sudoku::Cell::new(1, 2)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '6', '7')));
+    }
+
+    #[test]
+    fn test_v() {
+        // R4C5 and R4C6 are a 3 and a 2.
+        let constraint = VConstraint {
+            cells: vec![sudoku::Cell::new(3, 4), sudoku::Cell::new(3, 5)],
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '1', '2')));
+    }
+
+    #[test]
+    fn test_negative_xv() {
+        let sums = adjacent_pairs()
+            .map(|cells| {
+                (
+                    digit_at(GRID, cells[0]) + digit_at(GRID, cells[1]),
+                    cells.to_vec(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let xs = sums
+            .iter()
+            .filter(|(sum, _)| *sum == X_SUM)
+            .map(|(_, cells)| XConstraint {
+                cells: cells.clone(),
+            })
+            .collect::<Vec<_>>();
+        let vs = sums
+            .iter()
+            .filter(|(sum, _)| *sum == V_SUM)
+            .map(|(_, cells)| VConstraint {
+                cells: cells.clone(),
+            })
+            .collect::<Vec<_>>();
+        // Marks every X and V of the grid, except for the first `skipped` Xs.
+        let constraints = |skipped: usize| {
+            xs.iter()
+                .skip(skipped)
+                .map(|x| x as &dyn ConfigurableConstraint)
+                .chain(vs.iter().map(|v| v as &dyn ConfigurableConstraint))
+                .chain([&NegativeXVConstraint as &dyn ConfigurableConstraint])
+                .collect::<Vec<_>>()
+        };
+        assert!(satisfies(&constraints(0), GRID));
+        assert!(!satisfies(&constraints(1), GRID));
+    }
+}
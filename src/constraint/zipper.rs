@@ -0,0 +1,82 @@
+use crate::constraint::{renban, ConfigurableConstraint, Constraint};
+use crate::sudoku;
+use crate::sudoku::SudokuContext;
+use crate::ui::SudokuDrawContext;
+use crate::z3_helper::Assertions;
+use eframe::egui::{Context, Ui};
+use macros::DynClone;
+use z3::ast::Ast;
+
+#[derive(Default, DynClone)]
+#[dyn_clone(Constraint + Send)]
+pub struct ZipperConstraint {
+    cells: Vec<sudoku::Cell>,
+}
+
+impl Constraint for ZipperConstraint {
+    fn apply<'a>(&self, solver: &Assertions, context: &'a SudokuContext) {
+        // Pair up the cells from the ends of the line inwards, like PalindromeConstraint does.
+        let len = self.cells.len();
+        let pair_sums = (0..len / 2)
+            .map(|i| {
+                let (a, b) = (self.cells[i], self.cells[len - 1 - i]);
+                context.ints().alloc(z3::ast::Int::add(
+                    context.ctx(),
+                    &[
+                        context.get_cell(a.row, a.col),
+                        context.get_cell(b.row, b.col),
+                    ],
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        // An odd line has a middle cell without a partner, which gives the sum of every pair.
+        let total = if len % 2 == 1 {
+            let middle = self.cells[len / 2];
+            context.get_cell(middle.row, middle.col)
+        } else {
+            pair_sums[0]
+        };
+        for sum in pair_sums {
+            solver.assert(context.bools().alloc(sum._eq(total)));
+        }
+    }
+}
+
+impl ConfigurableConstraint for ZipperConstraint {
+    fn configure(&mut self, _ctx: &Context, _ui: &mut Ui) -> bool {
+        false
+    }
+
+    fn get_highlighted_cells(&mut self) -> Option<&mut Vec<sudoku::Cell>> {
+        Some(&mut self.cells)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.cells.len() >= 3
+    }
+
+    fn name(&self) -> &'static str {
+        "Zipper Line"
+    }
+
+    fn draw(&self, context: &SudokuDrawContext) {
+        renban::draw_line_between_cells(&self.cells, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::test_util::{satisfies, swap_digits, GRID};
+
+    #[test]
+    fn test_zipper() {
+        // R1C6 to R1C8 read 165, and 1 + 5 = 6.
+        let constraint = ZipperConstraint {
+            cells: (5..8).map(|col| sudoku::Cell::new(0, col)).collect(),
+        };
+        assert!(satisfies(&[&constraint], GRID));
+        assert!(!satisfies(&[&constraint], &swap_digits(GRID, '4', '5')));
+    }
+}
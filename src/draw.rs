@@ -0,0 +1,321 @@
+use eframe::egui;
+use std::cell::RefCell;
+use std::fmt::Write;
+
+// A backend-neutral drawing surface. Constraints draw through this (via SudokuDrawContext) so that
+// the same drawing code can be used for the egui widget and for exports.
+pub trait Canvas {
+    fn line_segment(&self, points: [egui::Pos2; 2], stroke: egui::Stroke);
+    fn dashed_line(
+        &self,
+        points: [egui::Pos2; 2],
+        stroke: egui::Stroke,
+        dash_length: f32,
+        gap_length: f32,
+    );
+    fn circle_filled(&self, center: egui::Pos2, radius: f32, color: egui::Color32);
+    fn circle_stroke(&self, center: egui::Pos2, radius: f32, stroke: egui::Stroke);
+    fn rect_filled(&self, rect: egui::Rect, rounding: f32, color: egui::Color32);
+    fn rect_stroke(&self, rect: egui::Rect, rounding: f32, stroke: egui::Stroke);
+    fn convex_polygon(&self, points: Vec<egui::Pos2>, color: egui::Color32);
+    fn text(
+        &self,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: &str,
+        size: f32,
+        color: egui::Color32,
+    );
+}
+
+pub struct EguiCanvas<'a> {
+    painter: &'a egui::Painter,
+}
+
+impl<'a> EguiCanvas<'a> {
+    pub fn new(painter: &'a egui::Painter) -> Self {
+        Self { painter }
+    }
+}
+
+impl<'a> Canvas for EguiCanvas<'a> {
+    fn line_segment(&self, points: [egui::Pos2; 2], stroke: egui::Stroke) {
+        self.painter.line_segment(points, stroke);
+    }
+
+    fn dashed_line(
+        &self,
+        points: [egui::Pos2; 2],
+        stroke: egui::Stroke,
+        dash_length: f32,
+        gap_length: f32,
+    ) {
+        self.painter.extend(egui::Shape::dashed_line(
+            &points,
+            stroke,
+            dash_length,
+            gap_length,
+        ));
+    }
+
+    fn circle_filled(&self, center: egui::Pos2, radius: f32, color: egui::Color32) {
+        self.painter.circle_filled(center, radius, color);
+    }
+
+    fn circle_stroke(&self, center: egui::Pos2, radius: f32, stroke: egui::Stroke) {
+        self.painter.circle_stroke(center, radius, stroke);
+    }
+
+    fn rect_filled(&self, rect: egui::Rect, rounding: f32, color: egui::Color32) {
+        self.painter.rect_filled(rect, rounding, color);
+    }
+
+    fn rect_stroke(&self, rect: egui::Rect, rounding: f32, stroke: egui::Stroke) {
+        self.painter.rect_stroke(rect, rounding, stroke);
+    }
+
+    fn convex_polygon(&self, points: Vec<egui::Pos2>, color: egui::Color32) {
+        self.painter.add(egui::epaint::PathShape::convex_polygon(
+            points,
+            color,
+            egui::Stroke::none(),
+        ));
+    }
+
+    fn text(
+        &self,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: &str,
+        size: f32,
+        color: egui::Color32,
+    ) {
+        self.painter
+            .text(pos, align, text, egui::FontId::proportional(size), color);
+    }
+}
+
+pub struct SvgCanvas {
+    width: f32,
+    height: f32,
+    elements: RefCell<String>,
+}
+
+impl SvgCanvas {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            elements: RefCell::new(String::new()),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.elements.into_inner(),
+            w = self.width,
+            h = self.height,
+        )
+    }
+
+    fn push(&self, element: std::fmt::Arguments) {
+        let mut elements = self.elements.borrow_mut();
+        elements.write_fmt(element).unwrap();
+        elements.push('\n');
+    }
+
+    fn paint(color: egui::Color32) -> String {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        if a == 255 {
+            format!("rgb({},{},{})", r, g, b)
+        } else {
+            format!("rgba({},{},{},{:.3})", r, g, b, a as f32 / 255.0)
+        }
+    }
+
+    fn stroke(stroke: egui::Stroke) -> String {
+        format!(
+            "fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+            Self::paint(stroke.color),
+            stroke.width
+        )
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn line_segment(&self, [from, to]: [egui::Pos2; 2], stroke: egui::Stroke) {
+        self.push(format_args!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-linecap=\"round\"/>",
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            Self::stroke(stroke)
+        ));
+    }
+
+    fn dashed_line(
+        &self,
+        [from, to]: [egui::Pos2; 2],
+        stroke: egui::Stroke,
+        dash_length: f32,
+        gap_length: f32,
+    ) {
+        self.push(format_args!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-dasharray=\"{} {}\"/>",
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            Self::stroke(stroke),
+            dash_length,
+            gap_length
+        ));
+    }
+
+    fn circle_filled(&self, center: egui::Pos2, radius: f32, color: egui::Color32) {
+        self.push(format_args!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+            center.x,
+            center.y,
+            radius,
+            Self::paint(color)
+        ));
+    }
+
+    fn circle_stroke(&self, center: egui::Pos2, radius: f32, stroke: egui::Stroke) {
+        self.push(format_args!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            center.x,
+            center.y,
+            radius,
+            Self::stroke(stroke)
+        ));
+    }
+
+    fn rect_filled(&self, rect: egui::Rect, rounding: f32, color: egui::Color32) {
+        self.push(format_args!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\"/>",
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height(),
+            rounding,
+            Self::paint(color)
+        ));
+    }
+
+    fn rect_stroke(&self, rect: egui::Rect, rounding: f32, stroke: egui::Stroke) {
+        self.push(format_args!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {}/>",
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height(),
+            rounding,
+            Self::stroke(stroke)
+        ));
+    }
+
+    fn convex_polygon(&self, points: Vec<egui::Pos2>, color: egui::Color32) {
+        let points = points
+            .iter()
+            .map(|point| format!("{},{}", point.x, point.y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.push(format_args!(
+            "<polygon points=\"{}\" fill=\"{}\"/>",
+            points,
+            Self::paint(color)
+        ));
+    }
+
+    fn text(
+        &self,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: &str,
+        size: f32,
+        color: egui::Color32,
+    ) {
+        let anchor = match align.x() {
+            egui::Align::Min => "start",
+            egui::Align::Center => "middle",
+            egui::Align::Max => "end",
+        };
+        let baseline = match align.y() {
+            egui::Align::Min => "hanging",
+            egui::Align::Center => "central",
+            egui::Align::Max => "text-after-edge",
+        };
+        self.push(format_args!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"{}\" dominant-baseline=\"{}\" fill=\"{}\">{}</text>",
+            pos.x,
+            pos.y,
+            size,
+            anchor,
+            baseline,
+            Self::paint(color),
+            Self::escape(text)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(f: impl FnOnce(&SvgCanvas)) -> String {
+        let canvas = SvgCanvas::new(100.0, 50.0);
+        f(&canvas);
+        canvas.finish()
+    }
+
+    #[test]
+    fn test_svg_line() {
+        let svg = draw(|canvas| {
+            canvas.line_segment(
+                [egui::pos2(1.0, 2.0), egui::pos2(3.5, 4.0)],
+                egui::Stroke::new(2f32, egui::Color32::RED),
+            )
+        });
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">\n"));
+        assert!(svg.contains("<line x1=\"1\" y1=\"2\" x2=\"3.5\" y2=\"4\" fill=\"none\" stroke=\"rgb(255,0,0)\" stroke-width=\"2\" stroke-linecap=\"round\"/>\n"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_svg_circle() {
+        let svg = draw(|canvas| {
+            canvas.circle_filled(egui::pos2(10.0, 20.0), 5.0, egui::Color32::BLACK);
+            canvas.circle_stroke(
+                egui::pos2(10.0, 20.0),
+                6.0,
+                egui::Stroke::new(1f32, egui::Color32::from_rgba_unmultiplied(0, 0, 255, 51)),
+            );
+        });
+        assert!(svg.contains("<circle cx=\"10\" cy=\"20\" r=\"5\" fill=\"rgb(0,0,0)\"/>\n"));
+        assert!(svg.contains("<circle cx=\"10\" cy=\"20\" r=\"6\" fill=\"none\" stroke=\"rgba(0,0,255,0.200)\" stroke-width=\"1\"/>\n"));
+    }
+
+    #[test]
+    fn test_svg_text() {
+        let svg = draw(|canvas| {
+            canvas.text(
+                egui::pos2(5.0, 6.0),
+                egui::Align2::LEFT_TOP,
+                "1 < 2 & 3",
+                12.0,
+                egui::Color32::WHITE,
+            )
+        });
+        assert!(svg.contains("<text x=\"5\" y=\"6\" font-family=\"sans-serif\" font-size=\"12\" text-anchor=\"start\" dominant-baseline=\"hanging\" fill=\"rgb(255,255,255)\">1 &lt; 2 &amp; 3</text>\n"));
+    }
+}
@@ -7,13 +7,23 @@
 #![feature(trait_upcasting)]
 
 mod z3_helper;
+mod bench;
 mod color;
 mod constraint;
+mod draw;
+mod solver;
 mod sudoku;
 mod ui;
 
 fn main() {
-    ui::run();
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let json = args.iter().any(|arg| arg == "--json");
+    args.retain(|arg| arg != "--json");
+    match args.as_slice() {
+        [command, corpus_path] if command == "bench" => bench::run(corpus_path, json),
+        [command] if command == "bench-renban" => bench::run_renban(json),
+        _ => ui::run(),
+    }
 }
 
 pub trait DynClone<T: ?Sized> {
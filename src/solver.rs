@@ -0,0 +1,545 @@
+use crate::constraint::{
+    Constraint, DigitDefinitionConstraint, GivenDigitConstraint, LatinSquareConstraint,
+    StandardBoxesConstraint,
+};
+use crate::sudoku::{Encoding, SudokuContext};
+use crate::z3_helper::{Assertions, BorrowedContext, OwnedContext};
+use ahash::{AHashMap, AHashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use z3::ast::Ast;
+
+// The solver configurations raced against each other, as (parameter, value) pairs. Which one is
+// fastest varies a lot between puzzles, so the first definitive answer is taken.
+const PORTFOLIO: [&[(&str, u32)]; 4] = [
+    &[],
+    &[("random_seed", 1), ("smt.phase_selection", 3)],
+    &[("random_seed", 2), ("smt.arith.solver", 2)],
+    &[("random_seed", 3), ("smt.relevancy", 0)],
+];
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum SolutionCount {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+pub enum SessionResult {
+    Sat(Vec<i32>, SolutionCount),
+    Unsat,
+    Unknown,
+    Superseded,
+}
+
+impl SessionResult {
+    fn is_definitive(&self) -> bool {
+        matches!(self, SessionResult::Sat(..) | SessionResult::Unsat)
+    }
+}
+
+// Statistics about the checks made for a request, from the worker whose result was used.
+#[derive(Clone, Default)]
+pub struct SolveStatistics {
+    pub worker: usize,
+    pub wall_time: Duration,
+    pub conflicts: u64,
+    pub decisions: u64,
+    pub memory_mb: f64,
+    // The number of top-level assertions made by each constraint.
+    pub assertions: Vec<(&'static str, usize)>,
+}
+
+impl SolveStatistics {
+    // The parameters of the portfolio configuration that produced the result.
+    pub fn configuration(&self) -> String {
+        let params = PORTFOLIO[self.worker];
+        if params.is_empty() {
+            return "Default".to_owned();
+        }
+        params
+            .iter()
+            .map(|(param, value)| format!("{}={}", param, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn to_json(&self) -> String {
+        let assertions = self
+            .assertions
+            .iter()
+            .map(|(name, count)| {
+                format!(
+                    "{{\"constraint\":\"{}\",\"count\":{}}}",
+                    name.replace('\\', "\\\\").replace('"', "\\\""),
+                    count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"worker\":{},\"wall_time_ms\":{:.3},\"conflicts\":{},\"decisions\":{},\"memory_mb\":{:.2},\"assertions\":[{}]}}",
+            self.worker,
+            self.wall_time.as_secs_f64() * 1000.0,
+            self.conflicts,
+            self.decisions,
+            self.memory_mb,
+            assertions
+        )
+    }
+}
+
+// Records the statistics of the checks made between `start` and `finish`. Z3's counters are
+// cumulative over the lifetime of a solver, so they are measured relative to the start.
+pub struct StatisticsRecorder {
+    start: Instant,
+    conflicts: f64,
+    decisions: f64,
+}
+
+impl StatisticsRecorder {
+    pub fn start(solver: &z3::Solver) -> Self {
+        Self {
+            start: Instant::now(),
+            conflicts: statistic(solver, "conflicts"),
+            decisions: statistic(solver, "decisions"),
+        }
+    }
+
+    pub fn finish(
+        self,
+        solver: &z3::Solver,
+        worker: usize,
+        assertions: Vec<(&'static str, usize)>,
+    ) -> SolveStatistics {
+        SolveStatistics {
+            worker,
+            wall_time: self.start.elapsed(),
+            conflicts: (statistic(solver, "conflicts") - self.conflicts) as u64,
+            decisions: (statistic(solver, "decisions") - self.decisions) as u64,
+            memory_mb: statistic(solver, "memory"),
+            assertions,
+        }
+    }
+}
+
+fn statistic(solver: &z3::Solver, key: &str) -> f64 {
+    match solver.get_statistics().value(key) {
+        Some(z3::StatisticsValue::UInt(value)) => value as f64,
+        Some(z3::StatisticsValue::Double(value)) => value,
+        None => 0.0,
+    }
+}
+
+// Asserts what `apply` asserts into the solver, returning how many assertions there were.
+pub fn count_assertions<'ctx>(
+    solver: &z3::Solver<'ctx>,
+    apply: impl FnOnce(&Assertions<'ctx>),
+) -> usize {
+    let assertions = Assertions::new();
+    apply(&assertions);
+    let assertions = assertions.into_vec();
+    for assertion in &assertions {
+        solver.assert(assertion);
+    }
+    assertions.len()
+}
+
+pub type NamedConstraint = (&'static str, Box<dyn Constraint + Send>);
+
+// A constraint sent to a session. The id identifies the constraint and the revision changes
+// whenever its settings do, so that workers can tell which constraints they have seen before.
+pub struct SessionConstraint {
+    pub id: u64,
+    pub revision: u64,
+    pub name: &'static str,
+    pub constraint: Box<dyn Constraint + Send>,
+}
+
+type ResultCallback = Box<dyn FnOnce(SessionResult, Option<SolveStatistics>) + Send>;
+
+// The result of a request, shared between all the workers checking it.
+struct PendingResult {
+    id: u64,
+    callback: Option<ResultCallback>,
+    remaining_workers: usize,
+    fallback: Option<(SessionResult, Option<SolveStatistics>)>,
+}
+
+impl PendingResult {
+    fn report(
+        this: &Mutex<PendingResult>,
+        result: SessionResult,
+        statistics: Option<SolveStatistics>,
+        workers: &[WorkerHandle],
+    ) {
+        let mut pending = this.lock().unwrap();
+        pending.remaining_workers -= 1;
+        let (result, statistics) = if result.is_definitive() {
+            (result, statistics)
+        } else {
+            if !matches!(pending.fallback, Some((SessionResult::Superseded, _))) {
+                pending.fallback = Some((result, statistics));
+            }
+            if pending.remaining_workers != 0 {
+                return;
+            }
+            pending.fallback.take().unwrap()
+        };
+
+        if let Some(callback) = pending.callback.take() {
+            let id = pending.id;
+            drop(pending);
+            for worker in workers {
+                worker.interrupt_if(|running_id| running_id == id);
+            }
+            callback(result, statistics);
+        }
+    }
+}
+
+struct SessionRequest {
+    id: u64,
+    givens: Vec<GivenDigitConstraint>,
+    constraints: Option<Vec<SessionConstraint>>,
+    max_solutions: usize,
+    result: Arc<Mutex<PendingResult>>,
+}
+
+#[derive(Default)]
+struct WorkerHandle {
+    running_id: Mutex<Option<u64>>,
+    cancel_handle: Mutex<Option<BorrowedContext>>,
+}
+
+impl WorkerHandle {
+    // Holding the lock on the running id prevents the worker from starting another check while
+    // this one is being interrupted.
+    fn interrupt_if(&self, predicate: impl FnOnce(u64) -> bool) {
+        let running_id = self.running_id.lock().unwrap();
+        if matches!(*running_id, Some(running_id) if predicate(running_id)) {
+            if let Some(handle) = self.cancel_handle.lock().unwrap().as_ref() {
+                handle.interrupt();
+            }
+        }
+    }
+}
+
+// A portfolio of solvers that live on their own threads for as long as the app is open. Each
+// worker asserts the base sudoku rules once. The assertions of each extra constraint are guarded by
+// a literal of their own and stay in the solver, so a constraint is only asserted the first time a
+// worker sees it, and enabling or disabling it only changes which literals are assumed in the
+// check. Once a constraint is edited, the guard of its previous revision is switched off for good.
+// The given digits are asserted in a push/pop scope that is replaced on every request.
+pub struct SolverSession {
+    senders: Vec<mpsc::Sender<SessionRequest>>,
+    workers: Arc<Vec<WorkerHandle>>,
+    next_id: u64,
+}
+
+impl SolverSession {
+    pub fn new(encoding: Encoding) -> Self {
+        let worker_count = thread::available_parallelism()
+            .map_or(1, |parallelism| parallelism.get())
+            .clamp(1, PORTFOLIO.len());
+        let workers: Arc<Vec<WorkerHandle>> =
+            Arc::new((0..worker_count).map(|_| WorkerHandle::default()).collect());
+        let senders = (0..worker_count)
+            .map(|index| {
+                let (sender, receiver) = mpsc::channel();
+                let workers = workers.clone();
+                thread::spawn(move || run_worker(receiver, workers, index, encoding));
+                sender
+            })
+            .collect();
+        Self {
+            senders,
+            workers,
+            next_id: 0,
+        }
+    }
+
+    // Submits a check of the grid with the given digits. If `constraints` is None, the constraints
+    // from the previous request are reused, otherwise it is called once per worker. Solutions are
+    // counted up to `max_solutions`. Any check still in progress is superseded.
+    pub fn submit(
+        &mut self,
+        givens: Vec<GivenDigitConstraint>,
+        constraints: Option<&dyn Fn() -> Vec<SessionConstraint>>,
+        max_solutions: usize,
+        callback: impl FnOnce(SessionResult, Option<SolveStatistics>) + Send + 'static,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let result = Arc::new(Mutex::new(PendingResult {
+            id,
+            callback: Some(Box::new(callback)),
+            remaining_workers: self.senders.len(),
+            fallback: None,
+        }));
+        for sender in &self.senders {
+            sender
+                .send(SessionRequest {
+                    id,
+                    givens: givens.clone(),
+                    constraints: constraints.map(|constraints| constraints()),
+                    max_solutions,
+                    result: result.clone(),
+                })
+                .expect("The solver session threads should be running");
+        }
+
+        for worker in self.workers.iter() {
+            worker.interrupt_if(|running_id| running_id < id);
+        }
+    }
+
+    pub fn interrupt(&self) {
+        for worker in self.workers.iter() {
+            worker.interrupt_if(|_| true);
+        }
+    }
+}
+
+fn run_worker(
+    receiver: mpsc::Receiver<SessionRequest>,
+    workers: Arc<Vec<WorkerHandle>>,
+    index: usize,
+    encoding: Encoding,
+) {
+    let worker = &workers[index];
+    let ctx = OwnedContext::new(z3::Context::new(&z3::Config::new()));
+    *worker.cancel_handle.lock().unwrap() = Some(ctx.make_borrowed());
+    let solver = z3::Solver::new(&ctx);
+    let mut params = z3::Params::new(&ctx);
+    for &(param, value) in PORTFOLIO[index] {
+        params.set_u32(param, value);
+    }
+    solver.set_params(&params);
+
+    let (base_names, base_constraints): (Vec<_>, Vec<_>) = base_constraints().into_iter().unzip();
+    let base_assertions = {
+        let sudoku = SudokuContext::create(&ctx, &base_constraints, encoding);
+        base_names
+            .into_iter()
+            .zip(&base_constraints)
+            .map(|(name, constraint)| {
+                let count =
+                    count_assertions(&solver, |solver| constraint.apply_encoded(solver, &sudoku));
+                (name, count)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut constraints: Vec<Box<dyn Constraint + Send>> = Vec::new();
+    let mut constraint_assertions = Vec::new();
+    let mut active_guards = Vec::new();
+    // The guard literal and assertion count of every constraint asserted so far, by id, revision
+    // and, for constraints that depend on the others, a hash of the constraints they were asserted
+    // alongside.
+    let mut guards: AHashMap<(u64, u64, u64), (z3::ast::Bool, usize)> = AHashMap::new();
+    solver.push();
+
+    let mut pending = None;
+    loop {
+        let mut request = match pending.take() {
+            Some(request) => request,
+            None => match receiver.recv() {
+                Ok(request) => request,
+                Err(_) => return,
+            },
+        };
+        // Only the latest request needs to be checked, but constraints from older requests still
+        // need to be applied if the latest request didn't change them.
+        while let Ok(newer) = receiver.try_recv() {
+            let older = std::mem::replace(&mut request, newer);
+            if request.constraints.is_none() {
+                request.constraints = older.constraints;
+            }
+            PendingResult::report(&older.result, SessionResult::Superseded, None, &workers);
+        }
+
+        solver.pop(1);
+        if let Some(new_constraints) = request.constraints {
+            let mut hasher = DefaultHasher::new();
+            for constraint in &new_constraints {
+                (constraint.id, constraint.revision).hash(&mut hasher);
+            }
+            let constraints_hash = hasher.finish();
+
+            let mut keys = Vec::new();
+            let mut names = Vec::new();
+            constraints.clear();
+            for constraint in new_constraints {
+                let dependency = if constraint.constraint.uses_other_constraints() {
+                    constraints_hash
+                } else {
+                    0
+                };
+                keys.push((constraint.id, constraint.revision, dependency));
+                names.push(constraint.name);
+                constraints.push(constraint.constraint);
+            }
+
+            // Earlier revisions of these constraints can't come back, so their guards are
+            // retired. Asserting a guard's negation lets the solver simplify its assertions away.
+            // Constraints that are missing might only be disabled, so their guards are kept.
+            let ids = keys.iter().map(|key| key.0).collect::<AHashSet<_>>();
+            guards.retain(|key, (guard, _)| {
+                let superseded = ids.contains(&key.0) && !keys.contains(key);
+                if superseded {
+                    solver.assert(&guard.not());
+                }
+                !superseded
+            });
+
+            let sudoku = SudokuContext::create(&ctx, &constraints, encoding);
+            active_guards.clear();
+            constraint_assertions.clear();
+            for ((key, name), constraint) in keys.into_iter().zip(names).zip(&constraints) {
+                let (guard, count) = guards.entry(key).or_insert_with(|| {
+                    let guard = z3::ast::Bool::fresh_const(&ctx, "constraint");
+                    let count = apply_guarded(&solver, &sudoku, constraint.as_ref(), &guard);
+                    (guard, count)
+                });
+                active_guards.push(guard.clone());
+                constraint_assertions.push((name, *count));
+            }
+        }
+        solver.push();
+
+        let sudoku = SudokuContext::create(&ctx, &constraints, encoding);
+        let given_assertions = count_assertions(&solver, |solver| {
+            for given in &request.givens {
+                given.apply_encoded(solver, &sudoku);
+            }
+        });
+        let recorder = StatisticsRecorder::start(&solver);
+
+        let check = || {
+            *worker.running_id.lock().unwrap() = Some(request.id);
+            let result = solver.check_assumptions(&active_guards);
+            *worker.running_id.lock().unwrap() = None;
+            result
+        };
+
+        let result = match check() {
+            z3::SatResult::Unsat => SessionResult::Unsat,
+            z3::SatResult::Unknown => match receiver.try_recv() {
+                Ok(newer) => {
+                    pending = Some(newer);
+                    SessionResult::Superseded
+                }
+                Err(_) => SessionResult::Unknown,
+            },
+            z3::SatResult::Sat => {
+                let solution = get_solution(&solver, &sudoku);
+
+                // Count further solutions by excluding each one found so far. The exclusions are
+                // asserted in the givens scope so they are dropped with the next request.
+                let mut solution_count = SolutionCount::Exactly(1);
+                let mut last_solution = solution.clone();
+                while let SolutionCount::Exactly(count) = solution_count {
+                    if count >= request.max_solutions {
+                        solution_count = SolutionCount::AtLeast(count);
+                        break;
+                    }
+                    exclude_solution(&solver, &sudoku, &last_solution);
+                    match check() {
+                        z3::SatResult::Unsat => break,
+                        z3::SatResult::Unknown => solution_count = SolutionCount::AtLeast(count),
+                        z3::SatResult::Sat => {
+                            last_solution = get_solution(&solver, &sudoku);
+                            solution_count = SolutionCount::Exactly(count + 1);
+                        }
+                    }
+                }
+
+                match receiver.try_recv() {
+                    Ok(newer) => {
+                        pending = Some(newer);
+                        SessionResult::Superseded
+                    }
+                    Err(_) => SessionResult::Sat(solution, solution_count),
+                }
+            }
+        };
+        let statistics = recorder.finish(
+            &solver,
+            index,
+            base_assertions
+                .iter()
+                .chain(&constraint_assertions)
+                .copied()
+                .chain([("Given digits", given_assertions)])
+                .collect(),
+        );
+        PendingResult::report(&request.result, result, Some(statistics), &workers);
+    }
+}
+
+// Asserts the constraint with each of its assertions implied by `guard`, so that it only applies
+// when `guard` is assumed. Returns the number of assertions.
+fn apply_guarded(
+    solver: &z3::Solver,
+    sudoku: &SudokuContext,
+    constraint: &(dyn Constraint + Send),
+    guard: &z3::ast::Bool,
+) -> usize {
+    let assertions = Assertions::new();
+    constraint.apply_encoded(&assertions, sudoku);
+    let assertions = assertions.into_vec();
+    for assertion in &assertions {
+        solver.assert(&guard.implies(assertion));
+    }
+    assertions.len()
+}
+
+pub fn base_constraints() -> Vec<NamedConstraint> {
+    vec![
+        ("Digits", Box::new(DigitDefinitionConstraint)),
+        ("Rows and columns", Box::new(LatinSquareConstraint)),
+        ("Boxes", Box::new(StandardBoxesConstraint)),
+    ]
+}
+
+pub fn get_solution(solver: &z3::Solver, sudoku: &SudokuContext) -> Vec<i32> {
+    let model = solver
+        .get_model()
+        .expect("The solver check should have passed");
+    let mut sol = vec![0; sudoku.width() * sudoku.height()];
+    for row in 0..sudoku.height() {
+        for col in 0..sudoku.width() {
+            sol[col + row * sudoku.width()] = model
+                .eval(sudoku.get_cell(row, col), true)
+                .unwrap()
+                .as_u64()
+                .unwrap() as i32;
+        }
+    }
+    sol
+}
+
+pub fn exclude_solution(solver: &z3::Solver, sudoku: &SudokuContext, solution: &[i32]) {
+    solver.assert(
+        sudoku.bools().alloc(
+            z3::ast::Bool::and(
+                sudoku.ctx(),
+                &(0..sudoku.height())
+                    .flat_map(|row| (0..sudoku.width()).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        sudoku.bools().alloc(
+                            sudoku
+                                .get_cell(row, col)
+                                ._eq(sudoku.const_int(solution[col + row * sudoku.width()])),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .not(),
+        ),
+    );
+}
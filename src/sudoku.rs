@@ -7,6 +7,27 @@ use crate::constraint::Constraint;
 
 pub const SUDOKU_SIZE: usize = 9;
 
+// How the digits in the cells are represented to the solver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    // Each cell is an integer, and constraints are expressed with integer arithmetic.
+    Integer,
+    // Each cell additionally has a boolean per digit, exactly one of which is true. Constraints
+    // that support it are expressed over the booleans, which are cheaper for the SAT core.
+    OneHot,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 2] = [Encoding::Integer, Encoding::OneHot];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Integer => "Integer",
+            Encoding::OneHot => "One-hot",
+        }
+    }
+}
+
 #[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Hash)]
 pub struct Cell {
     pub row: usize,
@@ -53,6 +74,24 @@ impl std::fmt::Display for Cell {
     }
 }
 
+// Parses the 1-based "r1c1" notation that cells are displayed in, in either case.
+impl std::str::FromStr for Cell {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (row, col) = s
+            .to_ascii_lowercase()
+            .strip_prefix('r')
+            .and_then(|s| s.split_once('c'))
+            .and_then(|(row, col)| Some((row.parse::<usize>().ok()?, col.parse::<usize>().ok()?)))
+            .ok_or(())?;
+        if !(1..=SUDOKU_SIZE).contains(&row) || !(1..=SUDOKU_SIZE).contains(&col) {
+            return Err(());
+        }
+        Ok(Cell::new(row - 1, col - 1))
+    }
+}
+
 pub struct SudokuContext<'a> {
     ctx: &'a z3::Context,
     bools: Z3Allocator<z3::ast::Bool<'a>>,
@@ -67,12 +106,18 @@ pub struct SudokuContext<'a> {
     height: usize,
     digits_range: RangeInclusive<usize>,
     cells: Vec<z3::ast::Int<'a>>,
+    encoding: Encoding,
+    digit_bools: Vec<z3::ast::Bool<'a>>,
 
     constraints: &'a [Box<dyn Constraint + Send>],
 }
 
 impl<'a> SudokuContext<'a> {
-    pub fn create(ctx: &'a z3::Context, constraints: &'a [Box<dyn Constraint + Send>]) -> Self {
+    pub fn create(
+        ctx: &'a z3::Context,
+        constraints: &'a [Box<dyn Constraint + Send>],
+        encoding: Encoding,
+    ) -> Self {
         Self {
             ctx,
             bools: Z3Allocator::new(),
@@ -84,11 +129,28 @@ impl<'a> SudokuContext<'a> {
             width: SUDOKU_SIZE,
             height: SUDOKU_SIZE,
             digits_range: 1..=SUDOKU_SIZE,
-            cells:
-                (0..SUDOKU_SIZE * SUDOKU_SIZE)
-                    .map(|_| z3::ast::Int::fresh_const(ctx, "C"))
-                    .collect()
-            ,
+            // Cells are named rather than fresh so that contexts created on the same z3 context
+            // refer to the same cells, which lets a solver be reused across contexts.
+            cells: (0..SUDOKU_SIZE * SUDOKU_SIZE)
+                .map(|i| {
+                    z3::ast::Int::new_const(
+                        ctx,
+                        Cell::new(i / SUDOKU_SIZE, i % SUDOKU_SIZE).to_string(),
+                    )
+                })
+                .collect(),
+            encoding,
+            digit_bools: match encoding {
+                Encoding::Integer => Vec::new(),
+                Encoding::OneHot => (0..SUDOKU_SIZE * SUDOKU_SIZE)
+                    .flat_map(|i| {
+                        (1..=SUDOKU_SIZE).map(move |digit| {
+                            let cell = Cell::new(i / SUDOKU_SIZE, i % SUDOKU_SIZE);
+                            z3::ast::Bool::new_const(ctx, format!("{}={}", cell, digit))
+                        })
+                    })
+                    .collect(),
+            },
             constraints,
         }
     }
@@ -142,6 +204,91 @@ impl<'a> SudokuContext<'a> {
         &self.cells[col + self.width() * row]
     }
 
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    // The boolean that is true iff the cell contains the digit. Only available in the one-hot
+    // encoding.
+    pub fn cell_is(&self, row: usize, col: usize, digit: usize) -> &z3::ast::Bool {
+        debug_assert!(
+            self.encoding == Encoding::OneHot,
+            "cell booleans are only available in the one-hot encoding"
+        );
+        debug_assert!(self.digits_range().contains(&digit), "digit {} is out of range", digit);
+        let digit_count = self.digits_range.end() - self.digits_range.start() + 1;
+        let cell_index = col + self.width() * row;
+        &self.digit_bools[cell_index * digit_count + digit - self.digits_range.start()]
+    }
+
+    // A formula that is true iff at most one of the booleans is true, using the pairwise encoding.
+    pub fn at_most_one<'b>(&'b self, bools: &[&'b z3::ast::Bool<'b>]) -> &'b z3::ast::Bool<'b> {
+        let mut clauses = Vec::new();
+        for (i, a) in bools.iter().enumerate() {
+            for b in &bools[i + 1..] {
+                clauses.push(self.bools().alloc(z3::ast::Bool::and(self.ctx(), &[a, b]).not()));
+            }
+        }
+        self.bools().alloc(z3::ast::Bool::and(self.ctx(), &clauses))
+    }
+
+    pub fn exactly_one<'b>(&'b self, bools: &[&'b z3::ast::Bool<'b>]) -> &'b z3::ast::Bool<'b> {
+        self.bools().alloc(z3::ast::Bool::and(
+            self.ctx(),
+            &[
+                self.bools().alloc(z3::ast::Bool::or(self.ctx(), bools)),
+                self.at_most_one(bools),
+            ],
+        ))
+    }
+
+    // The one-hot equivalent of a distinct over the cells. If there are as many cells as digits,
+    // every digit must appear exactly once, which gives the solver more to propagate on.
+    pub fn one_hot_distinct(&self, cells: &[Cell]) -> &z3::ast::Bool {
+        let digits = self.digits_range();
+        let is_house = cells.len() == digits.clone().count();
+        self.bools().alloc(z3::ast::Bool::and(
+            self.ctx(),
+            &digits
+                .map(|digit| {
+                    let bools = cells
+                        .iter()
+                        .map(|cell| self.cell_is(cell.row, cell.col, digit))
+                        .collect::<Vec<_>>();
+                    if is_house {
+                        self.exactly_one(&bools)
+                    } else {
+                        self.at_most_one(&bools)
+                    }
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    // The index of the region the cell belongs to. Regions are the standard boxes for now, but
+    // constraints that care about region boundaries should go through this rather than assuming
+    // 3x3 boxes.
+    pub fn region(&self, cell: Cell) -> usize {
+        cell.row / 3 * 3 + cell.col / 3
+    }
+
+    pub fn orthogonal_neighbours(&self, cell: Cell) -> Vec<Cell> {
+        let mut neighbours = Vec::new();
+        if cell.row != 0 {
+            neighbours.push(cell.up());
+        }
+        if cell.row + 1 < self.height {
+            neighbours.push(cell.down());
+        }
+        if cell.col != 0 {
+            neighbours.push(cell.left());
+        }
+        if cell.col + 1 < self.width {
+            neighbours.push(cell.right());
+        }
+        neighbours
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
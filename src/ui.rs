@@ -1,18 +1,35 @@
-use crate::constraint::{
-    ConfigurableConstraint, Constraint, DigitDefinitionConstraint, GivenDigitConstraint,
-    LatinSquareConstraint, StandardBoxesConstraint,
+use crate::constraint::{CellSelection, ConfigurableConstraint, GivenDigitConstraint};
+use crate::draw::{Canvas, EguiCanvas, SvgCanvas};
+use crate::solver::{
+    SessionConstraint, SessionResult, SolutionCount, SolveStatistics, SolverSession,
 };
-use crate::sudoku::{SudokuContext, SUDOKU_SIZE};
-use crate::z3_helper::{BorrowedContext, OwnedContext};
+use crate::sudoku::{Encoding, SUDOKU_SIZE};
 use crate::{color, constraint, sudoku};
 use eframe::egui;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
 
 struct ConstraintUi {
     color: egui::Color32,
+    enabled: bool,
     constraint: Box<dyn ConfigurableConstraint>,
+    // Identifies the constraint to the solver session, which reuses what it asserted for the same
+    // id and revision. The revision is bumped whenever the constraint is edited.
+    id: u64,
+    revision: u64,
+}
+
+impl ConstraintUi {
+    fn color(&self) -> egui::Color32 {
+        self.constraint.color().unwrap_or(self.color)
+    }
+}
+
+// Which parts of the puzzle have been edited since they were last sent to the solver.
+#[derive(Default)]
+struct Edits {
+    givens: bool,
+    constraints: bool,
 }
 
 pub const CELL_PADDING: f32 = 3.0;
@@ -24,8 +41,8 @@ pub struct SudokuDrawContext<'a> {
     top: f32,
     cell_size: f32,
     pub color: egui::Color32,
-    pub painter: &'a egui::Painter,
-    pub style: &'a egui::Style,
+    canvas: &'a dyn Canvas,
+    body_text_size: f32,
     default_draw: std::cell::Cell<bool>,
 }
 
@@ -38,8 +55,8 @@ impl<'a> SudokuDrawContext<'a> {
         top: f32,
         cell_size: f32,
         color: egui::Color32,
-        painter: &'a egui::Painter,
-        style: &'a egui::Style,
+        canvas: &'a dyn Canvas,
+        body_text_size: f32,
     ) -> Self {
         Self {
             width,
@@ -48,8 +65,8 @@ impl<'a> SudokuDrawContext<'a> {
             top,
             cell_size,
             color,
-            painter,
-            style,
+            canvas,
+            body_text_size,
             default_draw: std::cell::Cell::new(false),
         }
     }
@@ -64,15 +81,178 @@ impl<'a> SudokuDrawContext<'a> {
         )
     }
 
-    pub fn get_font(&self, cell_ratio: f32) -> egui::FontId {
-        let mut font = egui::TextStyle::Body.resolve(self.style);
-        font.size = (self.cell_size * cell_ratio).max(1.0);
-        font
+    pub fn text_size(&self, cell_ratio: f32) -> f32 {
+        (self.cell_size * cell_ratio).max(1.0)
+    }
+
+    pub fn body_text_size(&self) -> f32 {
+        self.body_text_size
     }
 
     pub fn default_draw(&self) {
         self.default_draw.set(true);
     }
+
+    pub fn line(&self, points: [egui::Pos2; 2], width: f32) {
+        self.canvas
+            .line_segment(points, egui::Stroke::new(width, self.color));
+    }
+
+    pub fn dashed_line(
+        &self,
+        points: [egui::Pos2; 2],
+        width: f32,
+        dash_length: f32,
+        gap_length: f32,
+    ) {
+        self.canvas.dashed_line(
+            points,
+            egui::Stroke::new(width, self.color),
+            dash_length,
+            gap_length,
+        );
+    }
+
+    pub fn circle_filled(&self, center: egui::Pos2, radius: f32) {
+        self.canvas.circle_filled(center, radius, self.color);
+    }
+
+    pub fn circle_stroke(&self, center: egui::Pos2, radius: f32, width: f32) {
+        self.canvas
+            .circle_stroke(center, radius, egui::Stroke::new(width, self.color));
+    }
+
+    pub fn rect_filled(&self, rect: egui::Rect) {
+        self.canvas.rect_filled(rect, 0.0, self.color);
+    }
+
+    pub fn convex_polygon(&self, points: Vec<egui::Pos2>) {
+        self.canvas.convex_polygon(points, self.color);
+    }
+
+    pub fn text(&self, pos: egui::Pos2, align: egui::Align2, text: &str, size: f32) {
+        self.canvas.text(pos, align, text, size, self.color);
+    }
+
+    pub fn text_with_color(
+        &self,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: &str,
+        size: f32,
+        color: egui::Color32,
+    ) {
+        self.canvas.text(pos, align, text, size, color);
+    }
+
+    pub fn arrow(&self, tail: egui::Pos2, tip: egui::Pos2, width: f32, head_length: f32) {
+        self.line([tail, tip], width);
+        self.arrow_head(tail, tip, width, head_length);
+    }
+
+    pub fn arrow_head(&self, from: egui::Pos2, tip: egui::Pos2, width: f32, head_length: f32) {
+        let rot = egui::emath::Rot2::from_angle(std::f32::consts::PI * 3.0 / 4.0);
+        let vec = (tip - from).normalized() * head_length;
+        self.line([tip, tip + rot * vec], width);
+        self.line([tip, tip + rot.inverse() * vec], width);
+    }
+
+    pub fn shade_cell(&self, row: usize, col: usize) {
+        self.canvas.rect_filled(
+            self.cell_rect(row, col),
+            0.0,
+            self.color.linear_multiply(0.25),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_constraints(
+    canvas: &dyn Canvas,
+    width: usize,
+    height: usize,
+    left: f32,
+    top: f32,
+    cell_size: f32,
+    body_text_size: f32,
+    extra_constraints: &mut [ConstraintUi],
+    selected_extra_constraint: Option<usize>,
+) {
+    let mut n_times_cell_constrained = vec![0; width * height];
+    let mut depth_sorted_constraints: Vec<_> = extra_constraints.iter_mut().enumerate().collect();
+    depth_sorted_constraints.sort_by_key(|(_, constraint)| -constraint.constraint.draw_depth());
+    for (constraint_index, constraint) in depth_sorted_constraints {
+        if selected_extra_constraint.contains(&constraint_index)
+            && !constraint.constraint.always_draw()
+        {
+            continue;
+        }
+
+        let context = SudokuDrawContext::new(
+            width,
+            height,
+            left,
+            top,
+            cell_size,
+            constraint.color(),
+            canvas,
+            body_text_size,
+        );
+        constraint.constraint.draw(&context);
+        if !context.default_draw.get() {
+            if let Some(cells) = constraint.constraint.get_highlighted_cells() {
+                for cell in cells {
+                    if n_times_cell_constrained[cell.col + width * cell.row] == 0 {
+                        n_times_cell_constrained[cell.col + width * cell.row] = 1;
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(cells) = constraint.constraint.get_highlighted_cells() {
+            for cell in cells {
+                let mut cell_rect = context.cell_rect(cell.row, cell.col);
+                n_times_cell_constrained[cell.col + width * cell.row] += 1;
+                let amt_to_shrink = ((2 * n_times_cell_constrained[cell.col + width * cell.row])
+                    as f32)
+                    .min(cell_rect.width() * 0.5 - 1.0);
+                cell_rect = cell_rect.shrink(amt_to_shrink);
+                canvas.rect_stroke(cell_rect, 2f32, egui::Stroke::new(1f32, context.color));
+            }
+        }
+    }
+}
+
+fn draw_grid_lines(
+    canvas: &dyn Canvas,
+    width: usize,
+    height: usize,
+    left: f32,
+    top: f32,
+    cell_size: f32,
+    color: egui::Color32,
+) {
+    for x in 0..=width {
+        let x_pos = left + x as f32 * cell_size;
+        canvas.line_segment(
+            [
+                egui::Pos2::new(x_pos, top),
+                egui::Pos2::new(x_pos, top + cell_size * height as f32),
+            ],
+            egui::Stroke::new(if x % 3 == 0 { 3f32 } else { 1f32 }, color),
+        );
+    }
+    for y in 0..=height {
+        let y_pos = top + y as f32 * cell_size;
+        canvas.line_segment(
+            [
+                egui::Pos2::new(left, y_pos),
+                egui::Pos2::new(left + cell_size * width as f32, y_pos),
+            ],
+            egui::Stroke::new(if y % 3 == 0 { 3f32 } else { 1f32 }, color),
+        );
+    }
 }
 
 struct SudokuWidget<'a> {
@@ -84,6 +264,7 @@ struct SudokuWidget<'a> {
     solving: bool,
     extra_constraints: &'a mut [ConstraintUi],
     selected_extra_constraint: Option<usize>,
+    edits: &'a mut Edits,
 }
 
 impl<'a> SudokuWidget<'a> {
@@ -97,6 +278,7 @@ impl<'a> SudokuWidget<'a> {
         solving: bool,
         extra_constraints: &'a mut [ConstraintUi],
         selected_extra_constraint: Option<usize>,
+        edits: &'a mut Edits,
     ) -> Self {
         assert_eq!(given_digits.len(), width * height);
         Self {
@@ -108,6 +290,7 @@ impl<'a> SudokuWidget<'a> {
             solving,
             extra_constraints,
             selected_extra_constraint,
+            edits,
         }
     }
 
@@ -120,6 +303,7 @@ impl<'a> SudokuWidget<'a> {
         assert!(row < self.width && col < self.height);
         self.given_digits[col + self.width * row] = digit;
         *self.solution.lock().unwrap() = None;
+        self.edits.givens = true;
     }
 
     fn cell_rect(left: f32, top: f32, cell_size: f32, row: usize, col: usize) -> egui::Rect {
@@ -129,6 +313,24 @@ impl<'a> SudokuWidget<'a> {
         )
     }
 
+    // The 2x2 block of cells around the interior grid corner of the clicked cell that is nearest
+    // to `pos`.
+    fn block_at_corner(
+        rect: egui::Rect,
+        row: usize,
+        col: usize,
+        pos: egui::Pos2,
+    ) -> Vec<sudoku::Cell> {
+        let corner_row = (row + (pos.y > rect.center().y) as usize).clamp(1, SUDOKU_SIZE - 1);
+        let corner_col = (col + (pos.x > rect.center().x) as usize).clamp(1, SUDOKU_SIZE - 1);
+        vec![
+            sudoku::Cell::new(corner_row - 1, corner_col - 1),
+            sudoku::Cell::new(corner_row - 1, corner_col),
+            sudoku::Cell::new(corner_row, corner_col - 1),
+            sudoku::Cell::new(corner_row, corner_col),
+        ]
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn draw_digit(
         left: f32,
@@ -137,16 +339,14 @@ impl<'a> SudokuWidget<'a> {
         row: usize,
         col: usize,
         digit: i32,
-        ui: &egui::Ui,
+        canvas: &dyn Canvas,
         color: egui::Color32,
     ) {
-        let mut font = egui::FontSelection::Default.resolve(ui.style());
-        font.size = cell_size * 0.8;
-        ui.painter().text(
+        canvas.text(
             Self::cell_rect(left, top, cell_size, row, col).center(),
             egui::Align2::CENTER_CENTER,
-            digit,
-            font,
+            &digit.to_string(),
+            cell_size * 0.8,
             color,
         );
     }
@@ -175,99 +375,49 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
             left += cell_size;
             top += cell_size;
 
-            let mut n_times_cell_constrained = vec![0; self.width * self.height];
-            let mut depth_sorted_constraints: Vec<_> =
-                self.extra_constraints.iter_mut().enumerate().collect();
-            depth_sorted_constraints
-                .sort_by_key(|(_, constraint)| -constraint.constraint.draw_depth());
-            for (constraint_index, constraint) in depth_sorted_constraints {
-                if self.selected_extra_constraint.contains(&constraint_index)
-                    && !constraint.constraint.always_draw()
-                {
-                    continue;
-                }
-
-                let context = SudokuDrawContext::new(
-                    self.width,
-                    self.height,
-                    left,
-                    top,
-                    cell_size,
-                    constraint.color,
-                    ui.painter(),
-                    ui.style(),
-                );
-                constraint.constraint.draw(&context);
-                if !context.default_draw.get() {
-                    if let Some(cells) = constraint.constraint.get_highlighted_cells() {
-                        for cell in cells {
-                            if n_times_cell_constrained[cell.col + self.width * cell.row] == 0 {
-                                n_times_cell_constrained[cell.col + self.width * cell.row] = 1;
-                            }
-                        }
-                    }
-                    continue;
-                }
-
-                if let Some(cells) = constraint.constraint.get_highlighted_cells() {
-                    for cell in cells {
-                        let mut cell_rect =
-                            Self::cell_rect(left, top, cell_size, cell.row, cell.col);
-                        n_times_cell_constrained[cell.col + self.width * cell.row] += 1;
-                        let amt_to_shrink = ((2 * n_times_cell_constrained
-                            [cell.col + self.width * cell.row])
-                            as f32)
-                            .min(cell_rect.width() * 0.5 - 1.0);
-                        cell_rect = cell_rect.shrink(amt_to_shrink);
-                        ui.painter().rect_stroke(
-                            cell_rect,
-                            2f32,
-                            egui::Stroke::new(1f32, constraint.color),
-                        );
-                    }
-                }
-            }
+            let canvas = EguiCanvas::new(ui.painter());
+            let body_text_size = egui::TextStyle::Body.resolve(ui.style()).size;
+            draw_constraints(
+                &canvas,
+                self.width,
+                self.height,
+                left,
+                top,
+                cell_size,
+                body_text_size,
+                self.extra_constraints,
+                self.selected_extra_constraint,
+            );
 
             if let Some(selected_constraint) = self.selected_extra_constraint {
                 let constraint = &mut self.extra_constraints[selected_constraint];
+                let color = constraint.color();
                 if let Some(cells) = constraint.constraint.get_highlighted_cells() {
                     for (index, cell) in cells.iter().enumerate() {
                         let cell_rect = Self::cell_rect(left, top, cell_size, cell.row, cell.col)
                             .shrink(CELL_PADDING);
-                        ui.painter().rect_stroke(
-                            cell_rect,
-                            2f32,
-                            egui::Stroke::new(3f32, constraint.color),
-                        );
+                        ui.painter()
+                            .rect_stroke(cell_rect, 2f32, egui::Stroke::new(3f32, color));
                         ui.painter().text(
                             cell_rect.min + egui::Vec2::splat(CELL_PADDING),
                             egui::Align2::LEFT_TOP,
                             index + 1,
                             egui::FontSelection::Default.resolve(ui.style()),
-                            constraint.color,
+                            color,
                         );
                     }
                 }
             }
 
-            for x in 0..=self.width {
-                let mut stroke = ui.style().visuals.widgets.noninteractive.fg_stroke;
-                stroke.width = if x % 3 == 0 { 3f32 } else { 1f32 };
-                ui.painter().vline(
-                    left + x as f32 * cell_size,
-                    top..=top + cell_size * self.height as f32,
-                    stroke,
-                );
-            }
-            for y in 0..=self.height {
-                let mut stroke = ui.style().visuals.widgets.noninteractive.fg_stroke;
-                stroke.width = if y % 3 == 0 { 3f32 } else { 1f32 };
-                ui.painter().hline(
-                    left..=left + cell_size * self.width as f32,
-                    top + y as f32 * cell_size,
-                    stroke,
-                );
-            }
+            draw_grid_lines(
+                &canvas,
+                self.width,
+                self.height,
+                left,
+                top,
+                cell_size,
+                ui.style().visuals.widgets.noninteractive.fg_stroke.color,
+            );
 
             let mut clicked_cell = false;
             for row in 0..self.height {
@@ -280,7 +430,7 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
                             row,
                             col,
                             digit,
-                            ui,
+                            &canvas,
                             ui.style().visuals.widgets.active.text_color(),
                         );
                     } else if let Some(solution) = self.solution.lock().unwrap().as_ref() {
@@ -291,7 +441,7 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
                             row,
                             col,
                             (*solution)[col + SUDOKU_SIZE * row],
-                            ui,
+                            &canvas,
                             if ui.style().visuals.dark_mode {
                                 egui::Color32::LIGHT_BLUE
                             } else {
@@ -311,11 +461,23 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
                         && !self.solving
                     {
                         if let Some(constraint_index) = self.selected_extra_constraint {
-                            let constraint =
-                                &mut self.extra_constraints[constraint_index].constraint;
+                            let constraint_ui = &mut self.extra_constraints[constraint_index];
+                            let constraint = &mut constraint_ui.constraint;
                             let max_highlighted_cells = constraint.get_max_highlighted_cells();
+                            let cell_selection = constraint.cell_selection();
                             if let Some(highlighted_cells) = constraint.get_highlighted_cells() {
-                                if let Some(existing_index) = highlighted_cells
+                                if cell_selection == CellSelection::Block {
+                                    let rect = Self::cell_rect(left, top, cell_size, row, col);
+                                    let block = cell_interaction
+                                        .interact_pointer_pos()
+                                        .map(|pos| Self::block_at_corner(rect, row, col, pos))
+                                        .unwrap_or_default();
+                                    if *highlighted_cells == block {
+                                        highlighted_cells.clear();
+                                    } else {
+                                        *highlighted_cells = block;
+                                    }
+                                } else if let Some(existing_index) = highlighted_cells
                                     .iter()
                                     .position(|c| c.row == row && c.col == col)
                                 {
@@ -326,6 +488,8 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
                                     }
                                     highlighted_cells.push(sudoku::Cell::new(row, col));
                                 }
+                                constraint_ui.revision += 1;
+                                self.edits.constraints = true;
                                 *self.selected_cell = None;
                                 clicked_cell = true;
                             }
@@ -443,7 +607,7 @@ impl<'a> egui::Widget for SudokuWidget<'a> {
 }
 
 enum SolveResult {
-    Ok,
+    Ok(Vec<i32>, SolutionCount),
     Unsolvable,
     TimedOut,
     Canceled,
@@ -453,7 +617,7 @@ enum SolveResult {
 impl SolveResult {
     fn message(&self) -> &'static str {
         match self {
-            SolveResult::Ok | SolveResult::Canceled => "",
+            SolveResult::Ok(..) | SolveResult::Canceled => "",
             SolveResult::Unsolvable => "Unsolvable",
             SolveResult::TimedOut => "Solver timed out",
             SolveResult::InvalidInput => "Invalid input",
@@ -461,6 +625,49 @@ impl SolveResult {
     }
 }
 
+// The status of the puzzle while setting, from the latest automatic check.
+enum LiveStatus {
+    Checking,
+    Broken,
+    Solutions(SolutionCount),
+}
+
+impl LiveStatus {
+    fn badge(&self) -> (String, egui::Color32) {
+        match self {
+            LiveStatus::Checking => ("Checking...".to_owned(), egui::Color32::GRAY),
+            LiveStatus::Broken => ("Broken".to_owned(), egui::Color32::LIGHT_RED),
+            LiveStatus::Solutions(SolutionCount::Exactly(1)) => {
+                ("Unique".to_owned(), egui::Color32::LIGHT_GREEN)
+            }
+            LiveStatus::Solutions(SolutionCount::Exactly(count)) => (
+                format!("Multiple solutions ({})", count),
+                egui::Color32::LIGHT_YELLOW,
+            ),
+            // The search for a second solution was interrupted or gave up before finding one.
+            LiveStatus::Solutions(SolutionCount::AtLeast(count)) if *count < 2 => (
+                "Solvable (uniqueness unknown)".to_owned(),
+                egui::Color32::LIGHT_BLUE,
+            ),
+            LiveStatus::Solutions(SolutionCount::AtLeast(count)) => (
+                format!("Multiple solutions ({}+)", count),
+                egui::Color32::LIGHT_YELLOW,
+            ),
+        }
+    }
+}
+
+// The latest automatic check and what it found.
+#[derive(Default)]
+struct LiveCheck {
+    // Incremented for every check, so that the results of checks that were replaced are ignored.
+    id: u64,
+    status: Option<LiveStatus>,
+}
+
+// How many solutions the automatic check counts before giving up on an exact count.
+const LIVE_SOLUTION_LIMIT: usize = 10;
+
 struct MyApp {
     grid: [Option<i32>; SUDOKU_SIZE * SUDOKU_SIZE],
     selected_cell: Option<sudoku::Cell>,
@@ -470,7 +677,16 @@ struct MyApp {
     extra_constraints: Vec<ConstraintUi>,
     selected_constraint: Option<usize>,
     error_message: Arc<Mutex<&'static str>>,
-    solver_cancel_handle: Arc<Mutex<Option<BorrowedContext>>>,
+    session: SolverSession,
+    encoding: Encoding,
+    auto_solve: bool,
+    live_check: Arc<Mutex<LiveCheck>>,
+    statistics: Arc<Mutex<Option<SolveStatistics>>>,
+    edits: Edits,
+    constraints_stale: bool,
+    next_constraint_id: u64,
+    // Where the last SVG export was written, or why it failed.
+    export_status: Option<String>,
 }
 
 impl MyApp {
@@ -484,98 +700,234 @@ impl MyApp {
             extra_constraints: Vec::new(),
             selected_constraint: None,
             error_message: Arc::new(Mutex::new("")),
-            solver_cancel_handle: Arc::new(Mutex::new(None)),
+            session: SolverSession::new(Encoding::Integer),
+            encoding: Encoding::Integer,
+            auto_solve: false,
+            live_check: Arc::new(Mutex::new(LiveCheck::default())),
+            statistics: Arc::new(Mutex::new(None)),
+            edits: Edits::default(),
+            constraints_stale: false,
+            next_constraint_id: 0,
+            export_status: None,
         }
     }
 
-    fn solve(&self, callback: impl FnOnce(SolveResult) + Send + Sync + 'static) {
+    // Submits the puzzle to the solver session. The extra constraints are only sent again if they
+    // were edited since they were last sent, or if `resend_constraints` is set.
+    fn solve(
+        &mut self,
+        resend_constraints: bool,
+        max_solutions: usize,
+        callback: impl FnOnce(SolveResult) + Send + 'static,
+    ) {
+        self.constraints_stale |= resend_constraints || self.edits.constraints;
+        self.edits = Edits::default();
+
         if self
             .extra_constraints
             .iter()
-            .any(|constraint| !constraint.constraint.is_valid())
+            .any(|constraint| constraint.enabled && !constraint.constraint.is_valid())
         {
             callback(SolveResult::InvalidInput);
-            self.solving.store(false, Ordering::Release);
             return;
         }
 
-        let mut constraints: Vec<Box<dyn Constraint + Send>> = Vec::new();
-        constraints.push(Box::new(DigitDefinitionConstraint));
-        constraints.push(Box::new(LatinSquareConstraint));
-        constraints.push(Box::new(StandardBoxesConstraint));
+        let mut givens = Vec::new();
         for row in 0..SUDOKU_SIZE {
             for col in 0..SUDOKU_SIZE {
                 if let Some(digit) = self.grid[col + SUDOKU_SIZE * row] {
-                    constraints.push(Box::new(GivenDigitConstraint {
+                    givens.push(GivenDigitConstraint {
                         row,
                         col,
                         value: digit,
-                    }));
+                    });
                 }
             }
         }
-        constraints.extend(
-            self.extra_constraints
+        let send_constraints = std::mem::take(&mut self.constraints_stale);
+        let extra_constraints = &self.extra_constraints;
+        let constraints = || -> Vec<SessionConstraint> {
+            extra_constraints
                 .iter()
-                .map(|constraint| constraint.constraint.dyn_clone()),
-        );
+                .filter(|constraint| constraint.enabled)
+                .map(|constraint| SessionConstraint {
+                    id: constraint.id,
+                    revision: constraint.revision,
+                    name: constraint.constraint.name(),
+                    constraint: constraint.constraint.dyn_clone(),
+                })
+                .collect()
+        };
 
-        let solution = self.solution.clone();
-        let solving = self.solving.clone();
+        let last_statistics = self.statistics.clone();
         let interrupted = self.interrupted.clone();
-        let solver_cancel_handle = self.solver_cancel_handle.clone();
         interrupted.store(false, Ordering::Release);
 
-        thread::spawn(move || {
-            let ctx = OwnedContext::new(z3::Context::new(&z3::Config::new()));
-            {
-                let mut guard = solver_cancel_handle.lock().unwrap();
-                *guard = Some(ctx.make_borrowed());
-            }
-            let sudoku = SudokuContext::create(&ctx, &constraints);
-            let solver = z3::Solver::new(sudoku.ctx());
-
-            for constraint in &constraints {
-                constraint.apply(&solver, &sudoku);
-            }
-
-            let result = solver.check();
-            match result {
-                z3::SatResult::Unsat => {
-                    callback(SolveResult::Unsolvable);
-                    solving.store(false, Ordering::Release);
-                    return;
+        let constraints =
+            send_constraints.then_some(&constraints as &dyn Fn() -> Vec<SessionConstraint>);
+        self.session.submit(
+            givens,
+            constraints,
+            max_solutions,
+            move |result, statistics| {
+                if statistics.is_some() {
+                    *last_statistics.lock().unwrap() = statistics;
                 }
-                z3::SatResult::Unknown => {
-                    callback(if interrupted.load(Ordering::Acquire) {
-                        SolveResult::Canceled
-                    } else {
-                        SolveResult::TimedOut
-                    });
-                    solving.store(false, Ordering::Release);
-                    return;
+                callback(match result {
+                    SessionResult::Sat(sol, solution_count) => SolveResult::Ok(sol, solution_count),
+                    SessionResult::Unsat => SolveResult::Unsolvable,
+                    SessionResult::Unknown => {
+                        if interrupted.load(Ordering::Acquire) {
+                            SolveResult::Canceled
+                        } else {
+                            SolveResult::TimedOut
+                        }
+                    }
+                    SessionResult::Superseded => SolveResult::Canceled,
+                });
+            },
+        );
+    }
+
+    // Writes the SVG to the working directory and describes the outcome, with the absolute
+    // path so that the file can be found.
+    // Writes to a new timestamped file in the working directory, never replacing an earlier export.
+    fn write_svg(svg: &str) -> String {
+        let dir = match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(err) => return format!("Failed to export SVG: {}", err),
+        };
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let mut attempt = 0;
+        loop {
+            let name = match attempt {
+                0 => format!("sudoku-{}.svg", timestamp),
+                _ => format!("sudoku-{}-{}.svg", timestamp, attempt),
+            };
+            let path = dir.join(name);
+            let file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path);
+            let result = match file {
+                Ok(mut file) => std::io::Write::write_all(&mut file, svg.as_bytes()),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    attempt += 1;
+                    continue;
                 }
-                z3::SatResult::Sat => {}
+                Err(err) => Err(err),
+            };
+            return match result {
+                Ok(()) => format!("Exported SVG to {}", path.display()),
+                Err(err) => format!("Failed to export SVG to {}: {}", path.display(), err),
             };
+        }
+    }
 
-            let model = solver
-                .get_model()
-                .expect("The solver check should have passed");
-            let mut sol = vec![0; SUDOKU_SIZE * SUDOKU_SIZE];
-            for row in 0..sudoku.height() {
-                for col in 0..sudoku.width() {
-                    let result = model
-                        .eval(sudoku.get_cell(row, col), true)
-                        .unwrap()
-                        .as_u64()
-                        .unwrap() as i32;
-                    sol[col + row * SUDOKU_SIZE] = result;
+    fn export_svg(&mut self, style: &egui::Style) -> String {
+        let cell_size = 50.0;
+        let canvas = SvgCanvas::new(
+            cell_size * (SUDOKU_SIZE + 2) as f32,
+            cell_size * (SUDOKU_SIZE + 2) as f32,
+        );
+        draw_constraints(
+            &canvas,
+            SUDOKU_SIZE,
+            SUDOKU_SIZE,
+            cell_size,
+            cell_size,
+            cell_size,
+            egui::TextStyle::Body.resolve(style).size,
+            &mut self.extra_constraints,
+            None,
+        );
+        draw_grid_lines(
+            &canvas,
+            SUDOKU_SIZE,
+            SUDOKU_SIZE,
+            cell_size,
+            cell_size,
+            cell_size,
+            egui::Color32::BLACK,
+        );
+        let solution = self.solution.lock().unwrap();
+        for row in 0..SUDOKU_SIZE {
+            for col in 0..SUDOKU_SIZE {
+                if let Some(digit) = self.grid[col + SUDOKU_SIZE * row] {
+                    SudokuWidget::draw_digit(
+                        cell_size,
+                        cell_size,
+                        cell_size,
+                        row,
+                        col,
+                        digit,
+                        &canvas,
+                        egui::Color32::BLACK,
+                    );
+                } else if let Some(solution) = solution.as_ref() {
+                    SudokuWidget::draw_digit(
+                        cell_size,
+                        cell_size,
+                        cell_size,
+                        row,
+                        col,
+                        solution[col + SUDOKU_SIZE * row],
+                        &canvas,
+                        egui::Color32::DARK_BLUE,
+                    );
                 }
             }
-            *solution.lock().unwrap() = Some(sol);
-            solving.store(false, Ordering::Release);
+        }
+        canvas.finish()
+    }
 
-            callback(SolveResult::Ok);
+    fn statistics_ui(&self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Solver statistics").show(ui, |ui| {
+            let statistics = self.statistics.lock().unwrap();
+            let statistics = match statistics.as_ref() {
+                Some(statistics) => statistics,
+                None => {
+                    ui.label("Nothing has been solved yet");
+                    return;
+                }
+            };
+            egui::Grid::new("solver_statistics_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Wall time");
+                    ui.label(format!(
+                        "{:.1} ms",
+                        statistics.wall_time.as_secs_f64() * 1000.0
+                    ));
+                    ui.end_row();
+                    ui.label("Conflicts");
+                    ui.label(statistics.conflicts.to_string());
+                    ui.end_row();
+                    ui.label("Decisions");
+                    ui.label(statistics.decisions.to_string());
+                    ui.end_row();
+                    ui.label("Memory");
+                    ui.label(format!("{:.1} MB", statistics.memory_mb));
+                    ui.end_row();
+                    ui.label("Solver configuration");
+                    ui.label(statistics.configuration());
+                    ui.end_row();
+                });
+            ui.add_space(5.0);
+            ui.label("Assertions per constraint");
+            egui::Grid::new("solver_assertions_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (name, count) in &statistics.assertions {
+                        ui.label(*name);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
         });
     }
 
@@ -603,8 +955,13 @@ impl MyApp {
                     );
                     self.extra_constraints.push(ConstraintUi {
                         color,
+                        enabled: true,
                         constraint: constraint::make_default_constraint(),
+                        id: self.next_constraint_id,
+                        revision: 0,
                     });
+                    self.next_constraint_id += 1;
+                    self.edits.constraints = true;
                 }
                 ui.add_space(5.0);
                 egui::ScrollArea::vertical()
@@ -614,13 +971,25 @@ impl MyApp {
                         for constraint_index in 0..self.extra_constraints.len() {
                             let mut should_break = false;
                             ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(
+                                        !solving,
+                                        egui::Checkbox::new(
+                                            &mut self.extra_constraints[constraint_index].enabled,
+                                            "",
+                                        ),
+                                    )
+                                    .changed()
+                                {
+                                    self.edits.constraints = true;
+                                }
                                 let selected = self.selected_constraint.contains(&constraint_index);
                                 let mut text = egui::RichText::new(
                                     self.extra_constraints[constraint_index].constraint.name(),
                                 );
                                 if !selected {
-                                    text =
-                                        text.color(self.extra_constraints[constraint_index].color);
+                                    text = text
+                                        .color(self.extra_constraints[constraint_index].color());
                                 }
                                 if ui.selectable_label(selected, text).clicked() {
                                     self.selected_constraint = Some(constraint_index);
@@ -637,6 +1006,7 @@ impl MyApp {
                                 {
                                     self.selected_constraint = None;
                                     self.extra_constraints.remove(constraint_index);
+                                    self.edits.constraints = true;
                                     should_break = true;
                                 }
                             });
@@ -657,8 +1027,12 @@ impl MyApp {
                 egui::ComboBox::from_id_source("constraint_type")
                     .selected_text(constraint.constraint.name())
                     .show_ui(ui, |ui| {
-                        let mut constraints: Vec<_> =
-                            constraint::CONFIGURABLES.keys().copied().collect();
+                        // Leave out aliases kept for old names.
+                        let mut constraints: Vec<_> = constraint::CONFIGURABLES
+                            .entries()
+                            .filter(|(name, create)| create().name() == **name)
+                            .map(|(name, _)| *name)
+                            .collect();
                         constraints.sort();
                         for constraint in constraints {
                             ui.selectable_value(&mut constraint_name, constraint, constraint);
@@ -669,8 +1043,12 @@ impl MyApp {
                     {
                         let mut new_constraint = constraint_creator();
                         let new_max_highlighted = new_constraint.get_max_highlighted_cells();
+                        // A block can't be made from arbitrary cells, so it is selected afresh.
+                        let keep_cells = new_constraint.cell_selection() == CellSelection::Cells;
                         if let (Some(new_highlighted), Some(old_highlighted)) = (
-                            new_constraint.get_highlighted_cells(),
+                            new_constraint
+                                .get_highlighted_cells()
+                                .filter(|_| keep_cells),
                             constraint.constraint.get_highlighted_cells(),
                         ) {
                             if new_max_highlighted < old_highlighted.len() {
@@ -681,10 +1059,17 @@ impl MyApp {
                             }
                         }
                         let color = constraint.color;
+                        let enabled = constraint.enabled;
+                        let id = constraint.id;
+                        let revision = constraint.revision + 1;
                         self.extra_constraints[selected_constraint] = ConstraintUi {
                             color,
+                            enabled,
                             constraint: new_constraint,
+                            id,
+                            revision,
                         };
+                        self.edits.constraints = true;
                         constraint = &mut self.extra_constraints[selected_constraint];
                     }
                 }
@@ -692,14 +1077,21 @@ impl MyApp {
                 ui.add_space(5.0);
 
                 if constraint.constraint.get_highlighted_cells().is_some() {
-                    if constraint.constraint.get_max_highlighted_cells() == 1 {
+                    if constraint.constraint.cell_selection() == CellSelection::Block {
+                        ui.label(
+                            "Right click near a grid corner to select the 2x2 block around it",
+                        );
+                    } else if constraint.constraint.get_max_highlighted_cells() == 1 {
                         ui.label("Right click to select the cell for this constraint");
                     } else {
                         ui.label("Right click to add cells to this constraint");
                     }
                     ui.add_space(5.0);
                 }
-                constraint.constraint.configure(ctx, ui);
+                if constraint.constraint.configure(ctx, ui) {
+                    constraint.revision += 1;
+                    self.edits.constraints = true;
+                }
             });
         }
     }
@@ -708,6 +1100,8 @@ impl MyApp {
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::SidePanel::right("constraints_panel").show(ctx, |ui| {
+            egui::TopBottomPanel::bottom("solver_statistics")
+                .show_inside(ui, |ui| self.statistics_ui(ui));
             self.extra_constraints_ui(ctx, ui);
             ui.set_min_width(ctx.available_rect().width() / 3.0);
         });
@@ -726,30 +1120,85 @@ impl eframe::App for MyApp {
                                 ui.button(egui::RichText::new("Cancel").font(solve_font));
                             if cancel_button.clicked() {
                                 self.interrupted.store(true, Ordering::Release);
-                                let guard = self.solver_cancel_handle.lock().unwrap();
-                                if let Some(handle) = guard.as_ref() {
-                                    handle.interrupt();
-                                }
+                                self.session.interrupt();
                             }
                             ui.add_space(cancel_button.rect.height() * 0.5);
                             ui.add(egui::Spinner::new().size(cancel_button.rect.height()));
                         } else {
                             if ui
-                                .button(egui::RichText::new("Solve").font(solve_font))
+                                .button(egui::RichText::new("Solve").font(solve_font.clone()))
                                 .clicked()
                                 && !self.solving.swap(true, Ordering::AcqRel)
                             {
                                 let error_message = self.error_message.clone();
+                                let solution = self.solution.clone();
+                                let solving = self.solving.clone();
                                 let ctx = ctx.clone();
-                                self.solve(move |result| {
+                                self.solve(true, 1, move |result| {
+                                    solving.store(false, Ordering::Release);
                                     *error_message.lock().unwrap() = result.message();
+                                    if let SolveResult::Ok(sol, _) = result {
+                                        *solution.lock().unwrap() = Some(sol);
+                                    }
                                     ctx.request_repaint();
                                 });
                             }
+                            if ui
+                                .button(egui::RichText::new("Export SVG").font(solve_font))
+                                .clicked()
+                            {
+                                let svg = self.export_svg(&ctx.style());
+                                self.export_status = Some(Self::write_svg(&svg));
+                            }
+                            let old_encoding = self.encoding;
+                            egui::ComboBox::from_id_source("encoding_combo")
+                                .selected_text(self.encoding.name())
+                                .show_ui(ui, |ui| {
+                                    for encoding in Encoding::ALL {
+                                        ui.selectable_value(
+                                            &mut self.encoding,
+                                            encoding,
+                                            encoding.name(),
+                                        );
+                                    }
+                                });
+                            if self.encoding != old_encoding {
+                                // The workers assert the base rules in their encoding up front, so
+                                // a new session is needed.
+                                self.session.interrupt();
+                                self.session = SolverSession::new(self.encoding);
+                                self.constraints_stale = true;
+                                self.edits.givens = true;
+                            }
+                            if ui
+                                .checkbox(&mut self.auto_solve, "Solve automatically")
+                                .changed()
+                            {
+                                self.edits.givens = true;
+                                self.live_check.lock().unwrap().status = None;
+                            }
+                            if self.auto_solve {
+                                if let Some(status) = &self.live_check.lock().unwrap().status {
+                                    let (text, color) = status.badge();
+                                    egui::Frame::none()
+                                        .fill(color)
+                                        .rounding(4.0)
+                                        .inner_margin(4.0)
+                                        .show(ui, |ui| {
+                                            ui.label(
+                                                egui::RichText::new(text)
+                                                    .color(egui::Color32::BLACK),
+                                            );
+                                        });
+                                }
+                            }
                             ui.heading(
                                 egui::RichText::new(*self.error_message.lock().unwrap())
                                     .color(ui.style().visuals.error_fg_color),
                             );
+                            if let Some(export_status) = &self.export_status {
+                                ui.label(export_status);
+                            }
                         }
                     });
                     ui.add_space(5.0);
@@ -777,12 +1226,47 @@ impl eframe::App for MyApp {
                                         self.solving.load(Ordering::Acquire),
                                         &mut self.extra_constraints,
                                         self.selected_constraint,
+                                        &mut self.edits,
                                     ));
                                 });
                             });
                     });
                 });
             });
+
+        if self.auto_solve
+            && (self.edits.givens || self.edits.constraints)
+            && !self.solving.load(Ordering::Acquire)
+        {
+            let error_message = self.error_message.clone();
+            let live_check = self.live_check.clone();
+            let ctx = ctx.clone();
+            let id = {
+                let mut live_check = live_check.lock().unwrap();
+                live_check.id += 1;
+                live_check.status = Some(LiveStatus::Checking);
+                live_check.id
+            };
+            // Only the badge is updated, so that the solution isn't given away while setting.
+            self.solve(false, LIVE_SOLUTION_LIMIT, move |result| {
+                let mut live_check = live_check.lock().unwrap();
+                if live_check.id != id {
+                    return;
+                }
+                *error_message.lock().unwrap() = result.message();
+                live_check.status = match result {
+                    SolveResult::Ok(_, solution_count) => {
+                        Some(LiveStatus::Solutions(solution_count))
+                    }
+                    SolveResult::Unsolvable => Some(LiveStatus::Broken),
+                    // Cancelled by the solve button, or there is no answer to show.
+                    SolveResult::Canceled | SolveResult::TimedOut | SolveResult::InvalidInput => {
+                        None
+                    }
+                };
+                ctx.request_repaint();
+            });
+        }
     }
 }
 
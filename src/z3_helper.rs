@@ -29,6 +29,16 @@ mod allocator {
             unsafe { &mut *self.things.get() }.push(heap as *mut T as *mut ());
             heap
         }
+
+        // Takes back everything allocated, in the order it was allocated.
+        pub fn into_vec(mut self) -> Vec<T> {
+            std::mem::take(self.things.get_mut())
+                .into_iter()
+                // Safety: These pointers come from Box::leak in the alloc method, and have been
+                // taken out of the allocator so that they aren't dropped again.
+                .map(|thing| *unsafe { Box::from_raw(thing as *mut T) })
+                .collect()
+        }
     }
 
     impl<T> Drop for Z3Allocator<T> {
@@ -83,5 +93,33 @@ mod context_wrapper {
     }
 }
 
+mod assertions {
+    use super::Z3Allocator;
+
+    // Collects the assertions made by constraints, so that they can be counted or guarded before
+    // they are asserted into a solver.
+    pub struct Assertions<'ctx> {
+        // An allocator rather than a Vec in a RefCell, so that this is covariant like z3::Solver.
+        assertions: Z3Allocator<z3::ast::Bool<'ctx>>,
+    }
+
+    impl<'ctx> Assertions<'ctx> {
+        pub fn new() -> Self {
+            Self {
+                assertions: Z3Allocator::new(),
+            }
+        }
+
+        pub fn assert(&self, assertion: &z3::ast::Bool<'ctx>) {
+            self.assertions.alloc(assertion.clone());
+        }
+
+        pub fn into_vec(self) -> Vec<z3::ast::Bool<'ctx>> {
+            self.assertions.into_vec()
+        }
+    }
+}
+
 pub use allocator::*;
+pub use assertions::*;
 pub use context_wrapper::*;